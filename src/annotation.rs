@@ -0,0 +1,240 @@
+use crate::ast::Expr;
+use crate::diagnostic::postprocess;
+use crate::eval;
+use crate::lex::lex;
+use crate::lex::trivia::{line_comments, trivia_width};
+use crate::parse;
+use crate::source::{Range, Source};
+use crate::syntax_node::SyntaxNode;
+
+// ソースコード中のコメントに書いた期待する結果
+// - "// ~ ERROR <message>" : その行で診断が報告される。<message> は "[E0003] unknown token" の一部
+//   コードの後ろに書けばその行を、コメントだけの行に書けば上の行を指す
+// - "// => <result>" : プログラム全体の評価結果。実行時エラーは "runtime error: <message>" と書く
+// e.g.
+//   let a = @; // ~ ERROR unknown token
+//   1 + 2
+//   // => 3
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Annotation {
+    Error { line: usize, message: String },
+    Result { line: usize, expected: String },
+}
+
+// 構文木の trivia から注釈を集める
+pub fn collect(source: &Source, node: &SyntaxNode) -> Vec<Annotation> {
+    let mut comments = vec![];
+    for (token, range) in node.tokens() {
+        let text_start = range.start + token.leading_trivia_width;
+        let text_end = text_start + token.token_width;
+        let trivia_ranges = [
+            Range {
+                start: range.start,
+                end: text_start,
+            },
+            Range {
+                start: text_end,
+                end: range.end,
+            },
+        ];
+        for trivia_range in trivia_ranges {
+            comments.append(&mut comments_in(source, trivia_range));
+        }
+    }
+
+    // コメントだけの行にある注釈の行番号
+    let annotation_only_lines: Vec<usize> = comments
+        .iter()
+        .filter(|comment| is_own_line(source, comment))
+        .map(|comment| source.line_column(comment.start).0)
+        .collect();
+
+    let mut annotations = vec![];
+    for comment in comments {
+        let text: String = source.get(&comment).iter().collect();
+        let body = text.trim_start_matches('/').trim();
+        let line = source.line_column(comment.start).0;
+        if let Some(message) = body
+            .strip_prefix('~')
+            .and_then(|rest| rest.trim_start().strip_prefix("ERROR"))
+        {
+            let mut line = line;
+            if is_own_line(source, &comment) {
+                line -= 1;
+                while annotation_only_lines.contains(&line) {
+                    line -= 1;
+                }
+            }
+            annotations.push(Annotation::Error {
+                line,
+                message: message.trim().to_string(),
+            });
+        } else if let Some(expected) = body.strip_prefix("=>") {
+            annotations.push(Annotation::Result {
+                line,
+                expected: expected.trim().to_string(),
+            });
+        }
+    }
+    annotations
+}
+
+// 構文エラーで読み飛ばしたトークンも trivia に含まれるので、字句解析しながらコメントを探す
+fn comments_in(source: &Source, range: Range) -> Vec<Range> {
+    let mut comments = vec![];
+    let mut range = range;
+    while !range.is_empty() {
+        comments.append(&mut line_comments(source, range));
+        range.start.advance(trivia_width(source, range));
+        if range.is_empty() {
+            break;
+        }
+        let token = lex(source, range).token;
+        range
+            .start
+            .advance(token.leading_trivia_width + token.token_width);
+    }
+    comments
+}
+
+fn is_own_line(source: &Source, comment: &Range) -> bool {
+    let (line, _) = source.line_column(comment.start);
+    let line_start = source.line_range(line).start;
+    source
+        .get(&Range {
+            start: line_start,
+            end: comment.start,
+        })
+        .iter()
+        .all(|c| c.is_whitespace())
+}
+
+// 注釈と実際の結果を比べ、食い違いを "<filename>:<line>: <message>" の形で返す
+pub fn check(filename: &str, source: &Source) -> Vec<String> {
+    let (node, diagnostics) = parse::parse(source);
+    let diagnostics = postprocess::postprocess(diagnostics);
+    let annotations = collect(source, &node);
+
+    let mut mismatches = vec![];
+    let mut expected_errors: Vec<(usize, &str)> = annotations
+        .iter()
+        .filter_map(|annotation| match annotation {
+            Annotation::Error { line, message } => Some((*line, message.as_str())),
+            Annotation::Result { .. } => None,
+        })
+        .collect();
+    for diagnostic in &diagnostics {
+        let (line, _) = source.line_column(diagnostic.range().start);
+        let msg = diagnostic.make_msg();
+        match expected_errors
+            .iter()
+            .position(|(expected_line, message)| *expected_line == line && msg.contains(message))
+        {
+            Some(i) => {
+                expected_errors.remove(i);
+            }
+            None => mismatches.push((line, format!("unexpected error: {msg}"))),
+        }
+    }
+    for (line, message) in expected_errors {
+        mismatches.push((line, format!("expected error not reported: {message}")));
+    }
+
+    for annotation in &annotations {
+        let Annotation::Result { line, expected } = annotation else {
+            continue;
+        };
+        if !diagnostics.is_empty() {
+            mismatches.push((
+                *line,
+                format!("expected result `{expected}`, but not evaluated because of errors"),
+            ));
+            continue;
+        }
+        let actual = match eval::eval(source, Expr::root(&node)) {
+            Ok(value) => value.to_string(),
+            Err(e) => format!("runtime error: {}", e.message()),
+        };
+        if actual != *expected {
+            mismatches.push((
+                *line,
+                format!("expected result `{expected}`, found `{actual}`"),
+            ));
+        }
+    }
+
+    mismatches.sort_by_key(|(line, _)| *line);
+    mismatches
+        .into_iter()
+        .map(|(line, message)| format!("{filename}:{line}: {message}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check, collect, Annotation};
+    use crate::parse::parse;
+    use crate::source::Source;
+
+    #[test]
+    fn test_collect() {
+        let source = Source::from_str(
+            "let a = @; // ~ ERROR unknown token\n1 +\n// ~ ERROR expected an expression\n// ~ ERROR E0002\n/* // => 0 */ // => 3",
+        );
+        let (node, _) = parse(&source);
+        assert_eq!(
+            collect(&source, &node),
+            vec![
+                Annotation::Error {
+                    line: 1,
+                    message: "unknown token".to_string()
+                },
+                Annotation::Error {
+                    line: 2,
+                    message: "expected an expression".to_string()
+                },
+                Annotation::Error {
+                    line: 2,
+                    message: "E0002".to_string()
+                },
+                Annotation::Result {
+                    line: 5,
+                    expected: "3".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comment_after_skipped_token() {
+        // '@' は trivia に含まれるが、その後ろのコメントも読む
+        let source = Source::from_str("1 @ // ~ ERROR unknown token");
+        assert!(check("main.denvl", &source).is_empty());
+    }
+
+    #[test]
+    fn test_check() {
+        let source = Source::from_str("1 + 2\n// => 3");
+        assert!(check("main.denvl", &source).is_empty());
+        let source = Source::from_str("let a = 1 / 0; a\n// => runtime error: division by zero");
+        assert!(check("main.denvl", &source).is_empty());
+
+        let source = Source::from_str("1 + 2\n// => 4");
+        assert_eq!(
+            check("main.denvl", &source),
+            vec!["main.denvl:2: expected result `4`, found `3`"]
+        );
+
+        let source = Source::from_str("let a = ;\n// => 1\n(2 // ~ ERROR unknown token");
+        assert_eq!(
+            check("main.denvl", &source),
+            vec![
+                "main.denvl:1: unexpected error: [E0006] expected an expression",
+                "main.denvl:2: expected result `1`, but not evaluated because of errors",
+                "main.denvl:3: unexpected error: [E0002] expected ')' to close the parenthesis",
+                "main.denvl:3: expected error not reported: unknown token",
+            ]
+        );
+    }
+}
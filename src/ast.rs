@@ -0,0 +1,679 @@
+use crate::source::{Position, Range, Source};
+use crate::syntax_node::{ChildNode, SyntaxNode, SyntaxToken, TokenKind};
+
+// SyntaxNode の上に被せる型付きの見方
+// エラー回復で補ったノードやトークンは None として見せるので、利用者は Error や幅 0 のトークンを気にしなくてよい
+// 各ノードは SyntaxNode への参照と開始位置だけを持ち、アクセサはメモリを確保しない
+// 子の位置は ChildNode が持つ幅を足して求めるので、アクセサは部分木を辿らない
+
+#[derive(Clone, Copy, Debug)]
+pub enum Expr<'a> {
+    Int(IntExpr<'a>),
+    Var(VarExpr<'a>),
+    Bool(BoolExpr<'a>),
+    Let(LetExpr<'a>),
+    If(IfExpr<'a>),
+    Lambda(LambdaExpr<'a>),
+    Call(CallExpr<'a>),
+    Bin(BinExpr<'a>),
+    Unary(UnaryExpr<'a>),
+    Paren(ParenExpr<'a>),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IntExpr<'a> {
+    node: &'a SyntaxNode,
+    offset: Position,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct VarExpr<'a> {
+    node: &'a SyntaxNode,
+    offset: Position,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BoolExpr<'a> {
+    node: &'a SyntaxNode,
+    offset: Position,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LetExpr<'a> {
+    node: &'a SyntaxNode,
+    offset: Position,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IfExpr<'a> {
+    node: &'a SyntaxNode,
+    offset: Position,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LambdaExpr<'a> {
+    node: &'a SyntaxNode,
+    offset: Position,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CallExpr<'a> {
+    node: &'a SyntaxNode,
+    offset: Position,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BinExpr<'a> {
+    node: &'a SyntaxNode,
+    offset: Position,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct UnaryExpr<'a> {
+    node: &'a SyntaxNode,
+    offset: Position,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParenExpr<'a> {
+    node: &'a SyntaxNode,
+    offset: Position,
+}
+
+// 変数名
+#[derive(Clone, Copy, Debug)]
+pub struct Name<'a> {
+    token: &'a SyntaxToken,
+    offset: Position,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Plus,
+    Not,
+}
+
+impl<'a> Expr<'a> {
+    // ソースコード全体を解析した構文木を式として見る
+    pub fn root(node: &'a SyntaxNode) -> Option<Self> {
+        Expr::cast(node, Position::start())
+    }
+
+    // offset から始まる node を式として見る。Error や Missing は式ではないので None
+    pub fn cast(node: &'a SyntaxNode, offset: Position) -> Option<Self> {
+        let expr = match node {
+            SyntaxNode::Int { .. } => Expr::Int(IntExpr { node, offset }),
+            SyntaxNode::Var { .. } => Expr::Var(VarExpr { node, offset }),
+            SyntaxNode::Bool { .. } => Expr::Bool(BoolExpr { node, offset }),
+            SyntaxNode::Let { .. } => Expr::Let(LetExpr { node, offset }),
+            SyntaxNode::If { .. } => Expr::If(IfExpr { node, offset }),
+            SyntaxNode::Lambda { .. } => Expr::Lambda(LambdaExpr { node, offset }),
+            SyntaxNode::Call { .. } => Expr::Call(CallExpr { node, offset }),
+            SyntaxNode::BinOp { .. } => Expr::Bin(BinExpr { node, offset }),
+            SyntaxNode::Unary { .. } => Expr::Unary(UnaryExpr { node, offset }),
+            SyntaxNode::Paren { .. } => Expr::Paren(ParenExpr { node, offset }),
+            SyntaxNode::Error { .. } | SyntaxNode::Missing { .. } => return None,
+        };
+        Some(expr)
+    }
+
+    pub fn syntax(&self) -> &'a SyntaxNode {
+        match self {
+            Expr::Int(IntExpr { node, .. })
+            | Expr::Var(VarExpr { node, .. })
+            | Expr::Bool(BoolExpr { node, .. })
+            | Expr::Let(LetExpr { node, .. })
+            | Expr::If(IfExpr { node, .. })
+            | Expr::Lambda(LambdaExpr { node, .. })
+            | Expr::Call(CallExpr { node, .. })
+            | Expr::Bin(BinExpr { node, .. })
+            | Expr::Unary(UnaryExpr { node, .. })
+            | Expr::Paren(ParenExpr { node, .. }) => node,
+        }
+    }
+
+    // trivia を含めた範囲
+    pub fn range(&self) -> Range {
+        let offset = match self {
+            Expr::Int(IntExpr { offset, .. })
+            | Expr::Var(VarExpr { offset, .. })
+            | Expr::Bool(BoolExpr { offset, .. })
+            | Expr::Let(LetExpr { offset, .. })
+            | Expr::If(IfExpr { offset, .. })
+            | Expr::Lambda(LambdaExpr { offset, .. })
+            | Expr::Call(CallExpr { offset, .. })
+            | Expr::Bin(BinExpr { offset, .. })
+            | Expr::Unary(UnaryExpr { offset, .. })
+            | Expr::Paren(ParenExpr { offset, .. }) => *offset,
+        };
+        Range {
+            start: offset,
+            end: offset + self.syntax().full_width(),
+        }
+    }
+}
+
+// Int や Var のようにトークン 1 つからなるノードのトークン
+fn single_token(node: &SyntaxNode) -> &SyntaxToken {
+    match node {
+        SyntaxNode::Int { token } | SyntaxNode::Var { token } | SyntaxNode::Bool { token } => token,
+        _ => unreachable!(),
+    }
+}
+
+impl IntExpr<'_> {
+    // 値が i64 に収まらなければ None
+    pub fn value(&self, source: &Source) -> Option<i64> {
+        text_chars(source, single_token(self.node), self.offset)
+            .iter()
+            .try_fold(0i64, |acc, c| {
+                acc.checked_mul(10)?.checked_add(c.to_digit(10)? as i64)
+            })
+    }
+
+    pub fn text_range(&self) -> Range {
+        text_range(single_token(self.node), self.offset)
+    }
+}
+
+impl<'a> VarExpr<'a> {
+    pub fn name(&self) -> Name<'a> {
+        Name {
+            token: single_token(self.node),
+            offset: self.offset,
+        }
+    }
+}
+
+impl BoolExpr<'_> {
+    pub fn value(&self) -> bool {
+        single_token(self.node).kind == TokenKind::True
+    }
+}
+
+impl<'a> LetExpr<'a> {
+    fn parts(
+        &self,
+    ) -> (
+        &'a SyntaxToken,
+        &'a SyntaxToken,
+        &'a SyntaxToken,
+        &'a ChildNode,
+        &'a SyntaxToken,
+        &'a ChildNode,
+    ) {
+        match self.node {
+            SyntaxNode::Let {
+                let_token,
+                ident_token,
+                equal_token,
+                init_expr,
+                semicolon_token,
+                body_expr,
+            } => (
+                let_token,
+                ident_token,
+                equal_token,
+                init_expr,
+                semicolon_token,
+                body_expr,
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    // 束縛する変数名。書かれていなければ None
+    pub fn name(&self) -> Option<Name<'a>> {
+        let (let_token, ident_token, ..) = self.parts();
+        if ident_token.token_width == 0 {
+            return None;
+        }
+        Some(Name {
+            token: ident_token,
+            offset: self.offset + let_token.full_width(),
+        })
+    }
+
+    pub fn init(&self) -> Option<Expr<'a>> {
+        let (let_token, ident_token, equal_token, init_expr, ..) = self.parts();
+        let offset = self.offset
+            + let_token.full_width()
+            + ident_token.full_width()
+            + equal_token.full_width();
+        Expr::cast(init_expr, offset)
+    }
+
+    pub fn body(&self) -> Option<Expr<'a>> {
+        let (let_token, ident_token, equal_token, init_expr, semicolon_token, body_expr) =
+            self.parts();
+        let offset = self.offset
+            + let_token.full_width()
+            + ident_token.full_width()
+            + equal_token.full_width()
+            + init_expr.full_width()
+            + semicolon_token.full_width();
+        Expr::cast(body_expr, offset)
+    }
+}
+
+impl<'a> IfExpr<'a> {
+    fn parts(
+        &self,
+    ) -> (
+        &'a SyntaxToken,
+        &'a ChildNode,
+        &'a SyntaxToken,
+        &'a ChildNode,
+        &'a SyntaxToken,
+        &'a ChildNode,
+    ) {
+        match self.node {
+            SyntaxNode::If {
+                if_token,
+                cond_expr,
+                then_token,
+                then_expr,
+                else_token,
+                else_expr,
+            } => (
+                if_token, cond_expr, then_token, then_expr, else_token, else_expr,
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn cond(&self) -> Option<Expr<'a>> {
+        let (if_token, cond_expr, ..) = self.parts();
+        Expr::cast(cond_expr, self.offset + if_token.full_width())
+    }
+
+    pub fn then_branch(&self) -> Option<Expr<'a>> {
+        let (if_token, cond_expr, then_token, then_expr, ..) = self.parts();
+        let offset =
+            self.offset + if_token.full_width() + cond_expr.full_width() + then_token.full_width();
+        Expr::cast(then_expr, offset)
+    }
+
+    pub fn else_branch(&self) -> Option<Expr<'a>> {
+        let (if_token, cond_expr, then_token, then_expr, else_token, else_expr) = self.parts();
+        let offset = self.offset
+            + if_token.full_width()
+            + cond_expr.full_width()
+            + then_token.full_width()
+            + then_expr.full_width()
+            + else_token.full_width();
+        Expr::cast(else_expr, offset)
+    }
+}
+
+impl<'a> LambdaExpr<'a> {
+    fn parts(
+        &self,
+    ) -> (
+        &'a SyntaxToken,
+        &'a SyntaxToken,
+        &'a SyntaxToken,
+        &'a ChildNode,
+    ) {
+        match self.node {
+            SyntaxNode::Lambda {
+                fun_token,
+                param_token,
+                arrow_token,
+                body_expr,
+            } => (fun_token, param_token, arrow_token, body_expr),
+            _ => unreachable!(),
+        }
+    }
+
+    // 仮引数名。書かれていなければ None
+    pub fn param(&self) -> Option<Name<'a>> {
+        let (fun_token, param_token, ..) = self.parts();
+        if param_token.token_width == 0 {
+            return None;
+        }
+        Some(Name {
+            token: param_token,
+            offset: self.offset + fun_token.full_width(),
+        })
+    }
+
+    pub fn body(&self) -> Option<Expr<'a>> {
+        let (fun_token, param_token, arrow_token, body_expr) = self.parts();
+        let offset = self.offset
+            + fun_token.full_width()
+            + param_token.full_width()
+            + arrow_token.full_width();
+        Expr::cast(body_expr, offset)
+    }
+}
+
+impl<'a> CallExpr<'a> {
+    fn parts(&self) -> (&'a ChildNode, &'a SyntaxToken, &'a ChildNode) {
+        match self.node {
+            SyntaxNode::Call {
+                callee_expr,
+                open_paren_token,
+                arg_expr,
+                ..
+            } => (callee_expr, open_paren_token, arg_expr),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn callee(&self) -> Option<Expr<'a>> {
+        let (callee_expr, ..) = self.parts();
+        Expr::cast(callee_expr, self.offset)
+    }
+
+    pub fn arg(&self) -> Option<Expr<'a>> {
+        let (callee_expr, open_paren_token, arg_expr) = self.parts();
+        Expr::cast(
+            arg_expr,
+            self.offset + callee_expr.full_width() + open_paren_token.full_width(),
+        )
+    }
+}
+
+impl<'a> BinExpr<'a> {
+    fn parts(&self) -> (&'a ChildNode, &'a SyntaxToken, &'a ChildNode) {
+        match self.node {
+            SyntaxNode::BinOp {
+                lhs_expr,
+                binop_token,
+                rhs_expr,
+            } => (lhs_expr, binop_token, rhs_expr),
+            _ => unreachable!(),
+        }
+    }
+
+    // 演算子が書かれていないときは、エラー回復でみなした演算子を返す
+    pub fn op(&self) -> BinOp {
+        let (_, binop_token, _) = self.parts();
+        match binop_token.kind {
+            TokenKind::Plus => BinOp::Add,
+            TokenKind::Minus => BinOp::Sub,
+            TokenKind::Ast => BinOp::Mul,
+            TokenKind::Slash => BinOp::Div,
+            TokenKind::EqualEqual => BinOp::Eq,
+            TokenKind::BangEqual => BinOp::Ne,
+            TokenKind::Lt => BinOp::Lt,
+            TokenKind::LtEqual => BinOp::Le,
+            TokenKind::Gt => BinOp::Gt,
+            TokenKind::GtEqual => BinOp::Ge,
+            TokenKind::AmpAmp => BinOp::And,
+            TokenKind::PipePipe => BinOp::Or,
+            _ => unreachable!("binary operator token"),
+        }
+    }
+
+    pub fn lhs(&self) -> Option<Expr<'a>> {
+        let (lhs_expr, ..) = self.parts();
+        Expr::cast(lhs_expr, self.offset)
+    }
+
+    pub fn rhs(&self) -> Option<Expr<'a>> {
+        let (lhs_expr, binop_token, rhs_expr) = self.parts();
+        Expr::cast(
+            rhs_expr,
+            self.offset + lhs_expr.full_width() + binop_token.full_width(),
+        )
+    }
+}
+
+impl<'a> UnaryExpr<'a> {
+    fn parts(&self) -> (&'a SyntaxToken, &'a ChildNode) {
+        match self.node {
+            SyntaxNode::Unary {
+                unop_token,
+                operand_expr,
+            } => (unop_token, operand_expr),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn op(&self) -> UnaryOp {
+        let (unop_token, _) = self.parts();
+        match unop_token.kind {
+            TokenKind::Minus => UnaryOp::Neg,
+            TokenKind::Plus => UnaryOp::Plus,
+            TokenKind::Bang => UnaryOp::Not,
+            _ => unreachable!("unary operator token"),
+        }
+    }
+
+    pub fn operand(&self) -> Option<Expr<'a>> {
+        let (unop_token, operand_expr) = self.parts();
+        Expr::cast(operand_expr, self.offset + unop_token.full_width())
+    }
+}
+
+impl<'a> ParenExpr<'a> {
+    pub fn inner(&self) -> Option<Expr<'a>> {
+        match self.node {
+            SyntaxNode::Paren {
+                open_paren_token,
+                inner_expr,
+                ..
+            } => Expr::cast(inner_expr, self.offset + open_paren_token.full_width()),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<'a> Name<'a> {
+    // trivia を除いた変数名の範囲
+    pub fn text_range(&self) -> Range {
+        text_range(self.token, self.offset)
+    }
+
+    pub fn text<'s>(&self, source: &'s Source) -> &'s [char] {
+        text_chars(source, self.token, self.offset)
+    }
+
+    pub fn is(&self, source: &Source, name: &str) -> bool {
+        self.text(source).iter().copied().eq(name.chars())
+    }
+}
+
+fn text_range(token: &SyntaxToken, offset: Position) -> Range {
+    let start = offset + token.leading_trivia_width;
+    Range {
+        start,
+        end: start + token.token_width,
+    }
+}
+
+fn text_chars<'s>(source: &'s Source, token: &SyntaxToken, offset: Position) -> &'s [char] {
+    source.get(&text_range(token, offset))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BinOp, Expr, UnaryOp};
+    use crate::parse::parse;
+    use crate::source::{Position, Range, Source};
+
+    #[test]
+    fn test_let() {
+        let source = Source::from_str("let x = 1 + 23; (x)");
+        let (node, _) = parse(&source);
+        let Some(Expr::Let(let_expr)) = Expr::root(&node) else {
+            panic!("let expression");
+        };
+        let name = let_expr.name().unwrap();
+        assert!(name.is(&source, "x"));
+        assert_eq!(
+            name.text_range(),
+            Range {
+                start: Position(4),
+                end: Position(5)
+            }
+        );
+
+        let Some(Expr::Bin(init)) = let_expr.init() else {
+            panic!("binary expression");
+        };
+        assert_eq!(init.op(), BinOp::Add);
+        let Some(Expr::Int(rhs)) = init.rhs() else {
+            panic!("integer");
+        };
+        assert_eq!(rhs.value(&source), Some(23));
+
+        let Some(Expr::Paren(body)) = let_expr.body() else {
+            panic!("parenthesized expression");
+        };
+        let Some(Expr::Var(var)) = body.inner() else {
+            panic!("variable");
+        };
+        assert!(var.name().is(&source, "x"));
+    }
+
+    #[test]
+    fn test_missing_parts() {
+        let source = Source::from_str("let = ; 1 *");
+        let (node, _) = parse(&source);
+        let Some(Expr::Let(let_expr)) = Expr::root(&node) else {
+            panic!("let expression");
+        };
+        assert!(let_expr.name().is_none());
+        assert!(let_expr.init().is_none());
+        let Some(Expr::Bin(body)) = let_expr.body() else {
+            panic!("binary expression");
+        };
+        assert_eq!(body.op(), BinOp::Mul);
+        assert!(body.lhs().is_some());
+        assert!(body.rhs().is_none());
+    }
+
+    #[test]
+    fn test_if() {
+        let source = Source::from_str("if a then 1 else b");
+        let (node, _) = parse(&source);
+        let Some(Expr::If(if_expr)) = Expr::root(&node) else {
+            panic!("if expression");
+        };
+        let Some(Expr::Var(cond)) = if_expr.cond() else {
+            panic!("variable");
+        };
+        assert!(cond.name().is(&source, "a"));
+        let Some(Expr::Int(then_branch)) = if_expr.then_branch() else {
+            panic!("integer");
+        };
+        assert_eq!(then_branch.value(&source), Some(1));
+        let Some(Expr::Var(else_branch)) = if_expr.else_branch() else {
+            panic!("variable");
+        };
+        assert_eq!(
+            else_branch.name().text_range(),
+            Range {
+                start: Position(17),
+                end: Position(18)
+            }
+        );
+    }
+
+    #[test]
+    fn test_lambda_and_call() {
+        let source = Source::from_str("(fun x -> x)(f)");
+        let (node, _) = parse(&source);
+        let Some(Expr::Call(call_expr)) = Expr::root(&node) else {
+            panic!("call expression");
+        };
+        let Some(Expr::Paren(callee)) = call_expr.callee() else {
+            panic!("parenthesized expression");
+        };
+        let Some(Expr::Lambda(lambda_expr)) = callee.inner() else {
+            panic!("lambda expression");
+        };
+        let param = lambda_expr.param().unwrap();
+        assert!(param.is(&source, "x"));
+        let Some(Expr::Var(body)) = lambda_expr.body() else {
+            panic!("variable");
+        };
+        assert_eq!(
+            body.name().text_range(),
+            Range {
+                start: Position(10),
+                end: Position(11)
+            }
+        );
+        let Some(Expr::Var(arg)) = call_expr.arg() else {
+            panic!("variable");
+        };
+        assert!(arg.name().is(&source, "f"));
+
+        let source = Source::from_str("fun -> 1");
+        let (node, _) = parse(&source);
+        let Some(Expr::Lambda(lambda_expr)) = Expr::root(&node) else {
+            panic!("lambda expression");
+        };
+        assert!(lambda_expr.param().is_none());
+    }
+
+    #[test]
+    fn test_unary() {
+        let source = Source::from_str("2 * - x");
+        let (node, _) = parse(&source);
+        let Some(Expr::Bin(bin_expr)) = Expr::root(&node) else {
+            panic!("binary expression");
+        };
+        let Some(Expr::Unary(unary_expr)) = bin_expr.rhs() else {
+            panic!("unary expression");
+        };
+        assert_eq!(unary_expr.op(), UnaryOp::Neg);
+        let Some(Expr::Var(var)) = unary_expr.operand() else {
+            panic!("variable");
+        };
+        assert!(var.name().is(&source, "x"));
+        assert_eq!(
+            var.name().text_range(),
+            Range {
+                start: Position(6),
+                end: Position(7)
+            }
+        );
+    }
+
+    #[test]
+    fn test_deep_let_chain() {
+        // 深い木でも、アクセサは子ノードが持つ幅から位置を求める
+        let n = 200;
+        let source = Source::from_str(&format!("{}a", "let a = 1;\n".repeat(n)));
+        let (node, _) = parse(&source);
+        let mut expr = Expr::root(&node).unwrap();
+        for _ in 0..n {
+            let Expr::Let(let_expr) = expr else {
+                panic!("let expression");
+            };
+            expr = let_expr.body().unwrap();
+        }
+        let Expr::Var(var) = expr else {
+            panic!("variable");
+        };
+        assert_eq!(
+            var.name().text_range(),
+            Range {
+                start: Position(11 * n),
+                end: Position(11 * n + 1)
+            }
+        );
+    }
+}
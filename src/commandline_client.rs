@@ -1,12 +1,19 @@
 use crate::consts;
 use crate::named_pipe::{self, NamedPipeClient, NamedPipeServer};
+use crate::server::{self, MessageFormat, Request};
+use denvl::annotation;
+use denvl::diagnostic::{code, suggestion};
+use denvl::source::Source;
+use denvl::syntax_node::dump;
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
-pub fn run(filename: &str) {
+pub fn run(filename: &str, color: &str, message_format: &str, max_errors: Option<usize>) {
     let name = PathBuf::from(consts::COMMON_NAME);
     let is_already_exists = NamedPipeServer::is_exists(&name);
     if is_already_exists.is_err() || !is_already_exists.unwrap() {
-        println!("launching server..");
+        // 起動の経過は JSON や SARIF の出力に混ざらないよう標準エラー出力に出す
+        eprintln!("launching server..");
         let exe_path = std::env::current_exe().unwrap();
         let status = std::process::Command::new(exe_path)
             .arg("__server")
@@ -25,23 +32,173 @@ pub fn run(filename: &str) {
             .unwrap();
         let line = named_pipe::readline(&mut file).unwrap();
         assert_eq!(consts::SERVER_STARTING_HEADER, line);
-        println!("done.");
+        eprintln!("done.");
     }
 
-    let mut input_filepath = std::env::current_dir().unwrap();
-    input_filepath = input_filepath.join(filename);
-    let input_filepath = input_filepath.into_os_string().into_string().unwrap();
+    let request = make_request(filename, color, message_format, max_errors);
 
     let mut client = NamedPipeClient::try_connect(name).unwrap();
 
-    client.writeline(input_filepath).unwrap();
+    client.writeline(request.to_line()).unwrap();
     loop {
         let line = client.readline().unwrap();
         if line == "done" {
             break;
         }
+        // JSON や SARIF の出力は他のツールに渡すので標準出力に出す
+        if request.message_format != MessageFormat::Human {
+            println!("{line}");
+        } else {
+            eprintln!("{line}");
+        }
+    }
+}
+
+// サーバーを介さずに構文解析だけを行い、エラーがあれば失敗ステータスで終了する
+pub fn check(filename: &str, color: &str, message_format: &str, max_errors: Option<usize>) {
+    let request = make_request(filename, color, message_format, max_errors);
+    let source = match Source::new(request.path.as_path()) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("failed to read {filename}. {e}");
+            std::process::exit(1);
+        }
+    };
+    let (_, diagnostics) = denvl::parse(&source);
+    let has_error = !diagnostics.is_empty();
+
+    for line in server::render_diagnostics(&request, &source, diagnostics) {
+        if request.message_format != MessageFormat::Human {
+            println!("{line}");
+        } else {
+            eprintln!("{line}");
+        }
+    }
+    if has_error {
+        std::process::exit(1);
+    }
+}
+
+// 機械的に適用できる修正案を全て適用してファイルを書き換え、残ったエラーを表示する
+// 改行コードなど修正案の及ばない部分は元のファイルのまま残す
+pub fn fix(filename: &str, color: &str) {
+    let request = make_request(filename, color, "human", None);
+    let original = match std::fs::read_to_string(&request.path) {
+        Ok(original) => original,
+        Err(e) => {
+            eprintln!("failed to read {filename}. {e}");
+            std::process::exit(1);
+        }
+    };
+    let source = Source::from_str(&original);
+    let (_, diagnostics) = denvl::parse(&source);
+    let suggestions: Vec<_> = diagnostics
+        .iter()
+        .flat_map(|diagnostic| diagnostic.suggestions())
+        .collect();
+    let (fixed, applied_count) = suggestion::apply_to_original(&original, &suggestions);
+    if applied_count > 0 {
+        if let Err(e) = std::fs::write(&request.path, fixed) {
+            eprintln!("failed to write {filename}. {e}");
+            std::process::exit(1);
+        }
+        eprintln!("fixed {applied_count} problem(s) in {filename}");
+    }
+
+    let source = Source::new(request.path.as_path()).expect("fail to read file");
+    let (_, diagnostics) = denvl::parse(&source);
+    let has_error = !diagnostics.is_empty();
+    for line in server::render_diagnostics(&request, &source, diagnostics) {
         eprintln!("{line}");
     }
+    if has_error {
+        std::process::exit(1);
+    }
+}
+
+pub fn parse(filename: &str, format: &str) {
+    let source = match Source::new(PathBuf::from(filename).as_path()) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("failed to read {filename}. {e}");
+            std::process::exit(1);
+        }
+    };
+    let (syntax_node, _) = denvl::parse(&source);
+    match format {
+        "json" => println!("{}", dump::to_json(filename, &source, &syntax_node)),
+        _ => println!("{}", dump::to_sexp(&source, &syntax_node)),
+    }
+}
+
+// ファイルごとに注釈を確かめ、食い違いがあれば失敗ステータスで終了する
+pub fn test(filenames: &[&String]) {
+    let mut failed_count = 0;
+    for filename in filenames {
+        let source = match Source::new(PathBuf::from(filename).as_path()) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("failed to read {filename}. {e}");
+                std::process::exit(1);
+            }
+        };
+        let mismatches = annotation::check(filename, &source);
+        if mismatches.is_empty() {
+            println!("{filename} ... ok");
+        } else {
+            println!("{filename} ... FAILED");
+            for mismatch in mismatches {
+                println!("  {mismatch}");
+            }
+            failed_count += 1;
+        }
+    }
+    println!(
+        "{} passed; {failed_count} failed",
+        filenames.len() - failed_count
+    );
+    if failed_count > 0 {
+        std::process::exit(1);
+    }
+}
+
+pub fn explain(error_code: &str) {
+    match code::find(error_code) {
+        Some(code) => print!("{}", code.explanation),
+        None => {
+            eprintln!("{error_code} is not a valid error code");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn make_request(
+    filename: &str,
+    color: &str,
+    message_format: &str,
+    max_errors: Option<usize>,
+) -> Request {
+    let mut input_filepath = std::env::current_dir().unwrap();
+    input_filepath = input_filepath.join(filename);
+    Request {
+        path: input_filepath,
+        color: use_color(color),
+        message_format: match message_format {
+            "json" => MessageFormat::Json,
+            "sarif" => MessageFormat::Sarif,
+            _ => MessageFormat::Human,
+        },
+        max_errors,
+    }
+}
+
+// サーバーは端末を持たないので、色を付けるかどうかはクライアント側で決める
+fn use_color(color: &str) -> bool {
+    match color {
+        "always" => true,
+        "never" => false,
+        _ => std::io::stderr().is_terminal(),
+    }
 }
 
 pub fn shutdown() {
@@ -1,6 +1,20 @@
+pub mod code;
+pub mod json;
+pub mod postprocess;
+pub mod render;
+pub mod sarif;
+pub mod suggestion;
+
 use crate::source::{Position, Range};
-use crate::syntax_node::TokenKind;
+use crate::syntax_node::{
+    TokenKind, BINARY_OPERATOR_TOKEN_KINDS, EXPR_BEGIN_TOKEN_KINDS, PRIMARY_EXPR_BEGIN_TOKEN_KINDS,
+};
+use suggestion::{Applicability, Suggestion};
 
+/// 字句解析・構文解析で見つかった問題
+///
+/// [`Diagnostic::range`] で問題の範囲を、[`Diagnostic::code`] と [`Diagnostic::message`] で内容を得る。
+/// 表示する前に [`postprocess::postprocess`] で重複や連鎖的なエラーを取り除くとよい
 #[derive(Debug, PartialEq, Eq)]
 pub enum Diagnostic {
     Error(DiagnosticError),
@@ -8,33 +22,292 @@ pub enum Diagnostic {
 }
 
 impl Diagnostic {
-    pub fn pos(&self) -> Position {
+    // 診断の対象となる範囲。トークンの抜けは幅 0 の範囲で表す
+    pub fn range(&self) -> Range {
         match self {
-            Diagnostic::Error(DiagnosticError::UnexpectedToken { range, .. }) => range.start,
-            Diagnostic::Error(DiagnosticError::MissedToken { pos, .. }) => *pos,
-            Diagnostic::Error(DiagnosticError::UnknownToken { range }) => range.start,
-            Diagnostic::Error(DiagnosticError::ExtraToken { range, .. }) => range.start,
-            Diagnostic::Error(DiagnosticError::Unknown { range }) => range.start,
-            Diagnostic::Warning(..) => Position::start(),
+            Diagnostic::Error(DiagnosticError::UnexpectedToken { range, .. }) => *range,
+            Diagnostic::Error(DiagnosticError::MissedToken { pos, .. })
+            | Diagnostic::Error(DiagnosticError::MissingExpression { pos }) => Range {
+                start: *pos,
+                end: *pos,
+            },
+            Diagnostic::Error(DiagnosticError::UnknownToken { range }) => *range,
+            Diagnostic::Error(DiagnosticError::ExtraToken { range, .. }) => *range,
+            Diagnostic::Error(DiagnosticError::Unknown { range }) => *range,
+            Diagnostic::Warning(warning) => match *warning {},
         }
     }
 
+    // 診断コード付きのメッセージ
     pub fn make_msg(&self) -> String {
+        format!("[{}] {}", self.code(), self.message())
+    }
+
+    pub fn message(&self) -> String {
         match self {
             Diagnostic::Error(DiagnosticError::UnexpectedToken {
                 expected, actual, ..
             }) => {
-                format!("unexpected token. expected {expected:?}, but actual is {actual:?}")
-            }
-            Diagnostic::Error(DiagnosticError::MissedToken { expected, .. }) => {
-                format!("missing expected token. expected {expected:?}")
+                format!("expected {}, found {actual}", describe_expected(expected))
             }
+            Diagnostic::Error(DiagnosticError::MissedToken {
+                expected, context, ..
+            }) => match context {
+                Some(context) => format!(
+                    "expected {} {}",
+                    describe_expected(expected),
+                    context.kind.phrase()
+                ),
+                None => format!("expected {}", describe_expected(expected)),
+            },
             Diagnostic::Error(DiagnosticError::UnknownToken { .. }) => "unknown token".to_string(),
-            Diagnostic::Error(DiagnosticError::ExtraToken { .. }) => "extra token".to_string(),
+            Diagnostic::Error(DiagnosticError::ExtraToken { kind, .. }) => format!("extra {kind}"),
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. }) => {
+                "expected an expression".to_string()
+            }
             Diagnostic::Error(DiagnosticError::Unknown { .. }) => "unknown error".to_string(),
-            Diagnostic::Warning(..) => todo!(),
+            Diagnostic::Warning(warning) => match *warning {},
+        }
+    }
+
+    // 利用者が検索するための安定した診断コード。説明は code::DIAGNOSTIC_CODES にある
+    pub fn code(&self) -> &'static str {
+        match self {
+            Diagnostic::Error(DiagnosticError::UnexpectedToken { .. }) => "E0001",
+            Diagnostic::Error(DiagnosticError::MissedToken { .. }) => "E0002",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. }) => "E0003",
+            Diagnostic::Error(DiagnosticError::ExtraToken { .. }) => "E0004",
+            Diagnostic::Error(DiagnosticError::Unknown { .. }) => "E0005",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. }) => "E0006",
+            Diagnostic::Warning(warning) => match *warning {},
+        }
+    }
+
+    // 機械的に扱うための診断の種類の名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Diagnostic::Error(DiagnosticError::UnexpectedToken { .. }) => "unexpected_token",
+            Diagnostic::Error(DiagnosticError::MissedToken { .. }) => "missed_token",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. }) => "unknown_token",
+            Diagnostic::Error(DiagnosticError::ExtraToken { .. }) => "extra_token",
+            Diagnostic::Error(DiagnosticError::Unknown { .. }) => "unknown",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. }) => "missing_expression",
+            Diagnostic::Warning(warning) => match *warning {},
         }
     }
+
+    pub fn severity(&self) -> &'static str {
+        match self {
+            Diagnostic::Error(..) => "error",
+            Diagnostic::Warning(..) => "warning",
+        }
+    }
+
+    // 診断の対象となる範囲に付ける説明
+    pub fn primary_label(&self) -> Label {
+        let message = match self {
+            Diagnostic::Error(DiagnosticError::UnexpectedToken { expected, .. })
+            | Diagnostic::Error(DiagnosticError::MissedToken { expected, .. }) => {
+                format!("expected {}", describe_expected(expected))
+            }
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. }) => {
+                "not a valid token".to_string()
+            }
+            Diagnostic::Error(DiagnosticError::ExtraToken { .. }) => {
+                "not expected here".to_string()
+            }
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. }) => {
+                "expected an expression".to_string()
+            }
+            Diagnostic::Error(DiagnosticError::Unknown { .. }) => String::new(),
+            Diagnostic::Warning(warning) => match *warning {},
+        };
+        Label {
+            range: self.range(),
+            message,
+        }
+    }
+
+    // 診断の原因に関係する別の位置 (e.g. 閉じられていない '(')
+    pub fn secondary_labels(&self) -> Vec<Label> {
+        match self {
+            Diagnostic::Error(DiagnosticError::MissedToken {
+                context: Some(context),
+                ..
+            }) => vec![Label::new(context.range, context.kind.label())],
+            _ => vec![],
+        }
+    }
+
+    pub fn notes(&self) -> Vec<String> {
+        match self {
+            Diagnostic::Error(DiagnosticError::UnexpectedToken { expected, .. })
+            | Diagnostic::Error(DiagnosticError::MissedToken { expected, .. })
+                if starts_expression(expected) =>
+            {
+                vec![expression_note()]
+            }
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. }) => {
+                vec![expression_note()]
+            }
+            _ => vec![],
+        }
+    }
+    pub fn helps(&self) -> Vec<String> {
+        self.suggestions()
+            .into_iter()
+            .map(|suggestion| suggestion.description)
+            .collect()
+    }
+
+    // 診断に対する修正案。構文解析のエラー回復で補ったトークンや読み飛ばしたトークンに対応する
+    pub fn suggestions(&self) -> Vec<Suggestion> {
+        use Applicability::*;
+        match self {
+            Diagnostic::Error(DiagnosticError::MissedToken { pos, expected, .. })
+                if expected.len() == 1 && expected[0].text().is_some() =>
+            {
+                let text = expected[0].text().unwrap();
+                // 予約語は前の式とくっつかないように空白を挟む。e.g. "if a 1" には " then" を挿入する
+                let text = if text.chars().all(|c| c.is_ascii_alphabetic()) {
+                    format!(" {text}")
+                } else {
+                    text.to_string()
+                };
+                vec![Suggestion::insert(*pos, &text, MachineApplicable)]
+            }
+            // 演算子の抜けはエラー回復で '+' とみなしているが、意図した演算子かどうかはわからない
+            Diagnostic::Error(DiagnosticError::MissedToken { pos, expected, .. })
+                if expected.contains(&TokenKind::Plus) =>
+            {
+                vec![Suggestion::insert(*pos, "+ ", MaybeIncorrect)]
+            }
+            Diagnostic::Error(DiagnosticError::UnknownToken { range }) => {
+                vec![Suggestion::remove(
+                    *range,
+                    "remove the unrecognized characters",
+                    MaybeIncorrect,
+                )]
+            }
+            // 余分なトークンは書き間違いかもしれないので、利用者の確認なしには消さない
+            Diagnostic::Error(DiagnosticError::ExtraToken { range, .. }) => {
+                vec![Suggestion::remove(
+                    *range,
+                    "remove this token",
+                    MaybeIncorrect,
+                )]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Label {
+    pub range: Range,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(range: Range, message: &str) -> Self {
+        Label {
+            range,
+            message: message.to_string(),
+        }
+    }
+}
+
+// トークンの抜けを見つけた文法上の文脈
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Context {
+    pub kind: ContextKind,
+    // 文脈となる構文を始めたトークンの範囲 (e.g. 'let' や対応する '(')
+    pub range: Range,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContextKind {
+    LetIdent,
+    LetEqual,
+    LetSemicolon,
+    IfThen,
+    IfElse,
+    FunParam,
+    FunArrow,
+    Paren,
+}
+
+impl ContextKind {
+    // "expected ... " に続けてメッセージを組み立てるための句
+    fn phrase(&self) -> &'static str {
+        match self {
+            ContextKind::LetIdent => "after 'let'",
+            ContextKind::LetEqual => "after the variable name in let binding",
+            ContextKind::LetSemicolon => "after the initializer in let binding",
+            ContextKind::IfThen => "after the condition of if expression",
+            ContextKind::IfElse => "after the then branch of if expression",
+            ContextKind::FunParam => "after 'fun'",
+            ContextKind::FunArrow => "after the parameter of function",
+            ContextKind::Paren => "to close the parenthesis",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ContextKind::LetIdent | ContextKind::LetEqual | ContextKind::LetSemicolon => {
+                "while parsing this let binding"
+            }
+            ContextKind::IfThen | ContextKind::IfElse => "while parsing this if expression",
+            ContextKind::FunParam | ContextKind::FunArrow => "while parsing this function",
+            ContextKind::Paren => "unclosed delimiter",
+        }
+    }
+}
+
+// 期待するトークンの集合を読みやすく表す
+// 式を始められるトークンは "an expression" に、二項演算子は "an operator" にまとめる
+fn describe_expected(expected: &[TokenKind]) -> String {
+    let mut groups = vec![];
+    let mut rest: Vec<&TokenKind> = expected.iter().collect();
+    if starts_expression(expected) {
+        rest.retain(|kind| !EXPR_BEGIN_TOKEN_KINDS.contains(kind));
+        groups.push("an expression".to_string());
+    }
+    if BINARY_OPERATOR_TOKEN_KINDS
+        .iter()
+        .all(|kind| expected.contains(kind))
+    {
+        rest.retain(|kind| !BINARY_OPERATOR_TOKEN_KINDS.contains(kind));
+        groups.push("an operator".to_string());
+    }
+    let mut alternatives: Vec<String> = rest.iter().map(|kind| kind.to_string()).collect();
+    alternatives.append(&mut groups);
+    join_alternatives(alternatives)
+}
+
+fn expression_note() -> String {
+    let kinds: Vec<String> = EXPR_BEGIN_TOKEN_KINDS
+        .iter()
+        .map(|kind| kind.to_string())
+        .collect();
+    format!("an expression starts with {}", join_alternatives(kinds))
+}
+
+// 一次式を始められるトークンが揃っていれば式の始まりとみなす
+// 'let' を置けない被演算子の位置や、前置演算子を含まない集合も式の始まりとする
+fn starts_expression(expected: &[TokenKind]) -> bool {
+    PRIMARY_EXPR_BEGIN_TOKEN_KINDS
+        .iter()
+        .all(|kind| expected.contains(kind))
+}
+
+fn join_alternatives(alternatives: Vec<String>) -> String {
+    match alternatives.as_slice() {
+        [] => "nothing".to_string(),
+        [alternative] => alternative.clone(),
+        [lhs, rhs] => format!("{lhs} or {rhs}"),
+        [init @ .., last] => format!("one of {} or {last}", init.join(", ")),
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -49,6 +322,7 @@ pub enum DiagnosticError {
         // 必要なトークンがない
         pos: Position,
         expected: Vec<TokenKind>,
+        context: Option<Context>,
     },
     UnknownToken {
         // 字句解析エラー
@@ -62,8 +336,14 @@ pub enum DiagnosticError {
     Unknown {
         range: Range,
     },
+    MissingExpression {
+        // 式が書かれていない (e.g. '1 + ' や '()')
+        pos: Position,
+    },
 }
 
+// 警告はまだ無い。値を作れない enum にしておき、警告を扱う箇所は `match *warning {}` と書く
+// 警告を追加すると、それらの箇所がコンパイルエラーになって扱いの漏れに気づける
 #[derive(Debug, PartialEq, Eq)]
 pub enum DiagnosticWarning {}
 
@@ -80,13 +360,146 @@ pub fn unexpected_token_error(
 }
 
 pub fn missed_token_error(pos: Position, expected: Vec<TokenKind>) -> Diagnostic {
-    Diagnostic::Error(DiagnosticError::MissedToken { pos, expected })
+    Diagnostic::Error(DiagnosticError::MissedToken {
+        pos,
+        expected,
+        context: None,
+    })
+}
+
+pub fn missed_token_error_with_context(
+    pos: Position,
+    expected: Vec<TokenKind>,
+    context: Context,
+) -> Diagnostic {
+    Diagnostic::Error(DiagnosticError::MissedToken {
+        pos,
+        expected,
+        context: Some(context),
+    })
 }
 
 pub fn unknown_token_error(range: Range) -> Diagnostic {
     Diagnostic::Error(DiagnosticError::UnknownToken { range })
 }
 
+pub fn missing_expression_error(pos: Position) -> Diagnostic {
+    Diagnostic::Error(DiagnosticError::MissingExpression { pos })
+}
+
 pub fn extra_token_error(range: Range, kind: TokenKind) -> Diagnostic {
     Diagnostic::Error(DiagnosticError::ExtraToken { range, kind })
 }
+
+#[cfg(test)]
+mod test {
+    use super::describe_expected;
+    use crate::parse::parse;
+    use crate::source::Source;
+    use crate::syntax_node::{TokenKind, BINARY_OPERATOR_TOKEN_KINDS};
+
+    fn messages(src: &str) -> Vec<String> {
+        let source = Source::from_str(src);
+        let (_, diagnostics) = parse(&source);
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message())
+            .collect()
+    }
+
+    #[test]
+    fn test_describe_expected() {
+        use TokenKind::*;
+        assert_eq!(describe_expected(&[Semicolon]), "';'");
+        assert_eq!(describe_expected(&[Ident]), "identifier");
+        assert_eq!(
+            describe_expected(&[Let, Ident, Number, True, False, OpenParen]),
+            "an expression"
+        );
+        assert_eq!(
+            describe_expected(&[Ident, Number, True, False, OpenParen]),
+            "an expression"
+        );
+        assert_eq!(
+            describe_expected(&[Semicolon, Let, Ident, Number, True, False, OpenParen]),
+            "';' or an expression"
+        );
+        assert_eq!(
+            describe_expected(&BINARY_OPERATOR_TOKEN_KINDS),
+            "an operator"
+        );
+        assert_eq!(describe_expected(&[Lt, Gt]), "'<' or '>'");
+        assert_eq!(
+            describe_expected(&[CloseParen, Semicolon, Equal]),
+            "one of ')', ';' or '='"
+        );
+    }
+
+    #[test]
+    fn test_messages_with_grammar_context() {
+        assert_eq!(
+            messages("let a 1; a"),
+            vec!["expected '=' after the variable name in let binding"]
+        );
+        assert_eq!(
+            messages("let = 1; a"),
+            vec!["expected identifier after 'let'"]
+        );
+        assert_eq!(
+            messages("(1"),
+            vec!["expected ')' to close the parenthesis"]
+        );
+        assert_eq!(messages("1 2"), vec!["expected an operator"]);
+        assert_eq!(messages("let let a = 1; a"), vec!["extra 'let'"]);
+        assert_eq!(
+            messages("if a let b = 1; b else 2"),
+            vec!["expected 'then' after the condition of if expression"]
+        );
+        assert_eq!(messages("if a then 1 ; else 2"), vec!["extra ';'"]);
+        assert_eq!(
+            messages("fun x x"),
+            vec!["expected '->' after the parameter of function"]
+        );
+        assert_eq!(
+            messages("fun -> 1"),
+            vec!["expected identifier after 'fun'"]
+        );
+        assert_eq!(
+            messages("f(1"),
+            vec!["expected ')' to close the parenthesis"]
+        );
+    }
+
+    #[test]
+    fn test_keyword_suggestion() {
+        use super::suggestion::apply;
+
+        let source = Source::from_str("if a let b = 1; b else 2");
+        let (_, diagnostics) = parse(&source);
+        let suggestions: Vec<_> = diagnostics
+            .iter()
+            .flat_map(|diagnostic| diagnostic.suggestions())
+            .collect();
+        // 予約語は前の式とくっつかないように空白を挟んで挿入する
+        assert_eq!(
+            apply(&source, &suggestions).0,
+            "if a then let b = 1; b else 2\n"
+        );
+    }
+
+    #[test]
+    fn test_extra_token_is_not_removed_by_fix() {
+        use super::suggestion::apply;
+
+        for src in ["let a = 1; a )", "fun 1", "let let a = 1; a"] {
+            let source = Source::from_str(src);
+            let (_, diagnostics) = parse(&source);
+            let suggestions: Vec<_> = diagnostics
+                .iter()
+                .flat_map(|diagnostic| diagnostic.suggestions())
+                .collect();
+            assert!(!suggestions.is_empty(), "{src:?}");
+            assert_eq!(apply(&source, &suggestions), (format!("{src}\n"), 0));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+// 診断の種類ごとの安定したコード
+// 利用者が検索できるよう、一度割り当てたコードは変更したり再利用したりしない
+
+pub struct DiagnosticCode {
+    pub code: &'static str,
+    // 機械的に扱うための名前
+    pub name: &'static str,
+    pub summary: &'static str,
+    // `denvl explain` で表示する説明。誤った例とその修正例を含む
+    pub explanation: &'static str,
+}
+
+pub const DIAGNOSTIC_CODES: [DiagnosticCode; 6] = [
+    DiagnosticCode {
+        code: "E0001",
+        name: "unexpected_token",
+        summary: "A token appeared where a different token was required.",
+        explanation: "\
+A token appeared where a different kind of token was required.
+
+Most often an expression was expected, but the token found cannot start one.
+
+Erroneous code example:
+
+    let a = 1; ; a
+
+The second ';' cannot start the body of the let binding. Remove it:
+
+    let a = 1; a
+",
+    },
+    DiagnosticCode {
+        code: "E0002",
+        name: "missed_token",
+        summary: "A required token is missing.",
+        explanation: "\
+A token required by the grammar is missing.
+
+The parser assumes the token is there and continues, so this error does not
+hide other errors after it.
+
+Erroneous code example:
+
+    (1 + 2
+
+The closing parenthesis is missing. Add it:
+
+    (1 + 2)
+",
+    },
+    DiagnosticCode {
+        code: "E0003",
+        name: "unknown_token",
+        summary: "Characters that do not form any token.",
+        explanation: "\
+Some characters do not form any token of denvl.
+
+denvl consists of integers, identifiers, the keywords 'let', 'if', 'then',
+'else', 'fun', 'true' and 'false', the symbols '+ - * / = ; ( ) == != < <= > >=
+&& || ! ->', whitespace and comments. Any other character is reported. A single
+'&' or '|' is not a token either; write '&&' or '||'.
+
+Erroneous code example:
+
+    1 + 2 @
+
+'@' is not a token. Remove it:
+
+    1 + 2
+",
+    },
+    DiagnosticCode {
+        code: "E0004",
+        name: "extra_token",
+        summary: "A token that is not needed here.",
+        explanation: "\
+A valid token appeared where nothing is expected.
+
+The parser skips the token and continues as if it were not written.
+
+Erroneous code example:
+
+    let let a = 1; a
+
+The second 'let' is not needed. Remove it:
+
+    let a = 1; a
+",
+    },
+    DiagnosticCode {
+        code: "E0005",
+        name: "unknown",
+        summary: "An error that is not classified.",
+        explanation: "\
+The compiler found a problem it cannot classify.
+
+This error has no dedicated explanation because it should not be reported for
+any program. If you see it, please report the program that caused it.
+",
+    },
+    DiagnosticCode {
+        code: "E0006",
+        name: "missing_expression",
+        summary: "An expression is required but not written.",
+        explanation: "\
+An expression is required but nothing is written.
+
+This happens after a binary or prefix operator, after '=' or ';' of a let
+binding, after 'if', 'then' or 'else', after '->' of a function, and between
+parentheses, including the argument of a function call.
+
+Erroneous code example:
+
+    let a = ; a
+
+The initializer of the let binding is missing. Write an expression:
+
+    let a = 1; a
+",
+    },
+];
+
+pub fn find(code: &str) -> Option<&'static DiagnosticCode> {
+    DIAGNOSTIC_CODES
+        .iter()
+        .find(|diagnostic_code| diagnostic_code.code.eq_ignore_ascii_case(code))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find, DIAGNOSTIC_CODES};
+    use crate::diagnostic::{
+        extra_token_error, missed_token_error, missing_expression_error, unexpected_token_error,
+        unknown_token_error, Diagnostic, DiagnosticError,
+    };
+    use crate::source::{Position, Range};
+    use crate::syntax_node::TokenKind;
+
+    fn range() -> Range {
+        Range {
+            start: Position(0),
+            end: Position(1),
+        }
+    }
+
+    #[test]
+    fn test_every_diagnostic_has_explanation() {
+        let diagnostics = vec![
+            unexpected_token_error(range(), vec![], TokenKind::Error),
+            missed_token_error(Position(0), vec![]),
+            unknown_token_error(range()),
+            extra_token_error(range(), TokenKind::Let),
+            Diagnostic::Error(DiagnosticError::Unknown { range: range() }),
+            missing_expression_error(Position(0)),
+        ];
+        for diagnostic in diagnostics {
+            let code = find(diagnostic.code()).expect("diagnostic code is registered");
+            assert_eq!(code.name, diagnostic.name());
+            assert!(!code.explanation.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_codes_are_unique() {
+        for (i, lhs) in DIAGNOSTIC_CODES.iter().enumerate() {
+            for rhs in &DIAGNOSTIC_CODES[i + 1..] {
+                assert_ne!(lhs.code, rhs.code);
+                assert_ne!(lhs.name, rhs.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find("E0003").unwrap().name, "unknown_token");
+        assert_eq!(find("e0003").unwrap().name, "unknown_token");
+        assert!(find("E9999").is_none());
+    }
+}
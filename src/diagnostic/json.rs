@@ -0,0 +1,141 @@
+use super::{Diagnostic, DiagnosticError};
+use crate::source::{Position, Source};
+use serde_json::{json, Value};
+
+// 診断情報をエディタや CI から読むための JSON に変換する
+// e.g.
+// {"actual":null,"code":"E0002","end":{"column":3,"line":1,"offset":2},
+//  "expected":["CloseParen"],"file":"main.denvl","message":"...","name":"missed_token",
+//  "severity":"error","start":{"column":3,"line":1,"offset":2},
+//  "suggestions":[{"applicability":"machine-applicable","description":"insert ')'",...}]}
+pub fn render(filename: &str, source: &Source, diagnostic: &Diagnostic) -> String {
+    to_value(filename, source, diagnostic).to_string()
+}
+
+pub fn to_value(filename: &str, source: &Source, diagnostic: &Diagnostic) -> Value {
+    let range = diagnostic.range();
+    let (expected, actual) = match diagnostic {
+        Diagnostic::Error(DiagnosticError::UnexpectedToken {
+            expected, actual, ..
+        }) => (Some(expected), Some(actual)),
+        Diagnostic::Error(DiagnosticError::MissedToken { expected, .. }) => (Some(expected), None),
+        Diagnostic::Error(DiagnosticError::ExtraToken { kind, .. }) => (None, Some(kind)),
+        _ => (None, None),
+    };
+    json!({
+        "severity": diagnostic.severity(),
+        "code": diagnostic.code(),
+        "name": diagnostic.name(),
+        "message": diagnostic.message(),
+        "file": filename,
+        "start": location(source, range.start),
+        "end": location(source, range.end),
+        "expected": expected.map(|kinds| {
+            kinds.iter().map(|kind| kind.name()).collect::<Vec<_>>()
+        }),
+        "actual": actual.map(|kind| kind.name()),
+        "suggestions": diagnostic.suggestions().iter().map(|suggestion| {
+            json!({
+                "description": suggestion.description,
+                "applicability": suggestion.applicability.name(),
+                "start": location(source, suggestion.edit.range.start),
+                "end": location(source, suggestion.edit.range.end),
+                "text": suggestion.edit.text,
+            })
+        }).collect::<Vec<_>>(),
+    })
+}
+
+// 行番号と列番号は 1 origin、offset は元のファイル先頭からの文字数 ("\r\n" の '\r' も数える)
+fn location(source: &Source, pos: Position) -> Value {
+    let (line, column) = source.line_column(pos);
+    json!({
+        "line": line,
+        "column": column,
+        "offset": source.original_offset(pos),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{render, to_value};
+    use crate::diagnostic::{missed_token_error, unexpected_token_error, unknown_token_error};
+    use crate::source::{Position, Range, Source};
+    use crate::syntax_node::TokenKind;
+    use serde_json::json;
+
+    #[test]
+    fn test_missed_token() {
+        let source = Source::from_str("let a = 1;\n(a");
+        let diagnostic = missed_token_error(Position(13), vec![TokenKind::CloseParen]);
+        assert_eq!(
+            to_value("main.denvl", &source, &diagnostic),
+            json!({
+                "severity": "error",
+                "code": "E0002",
+                "name": "missed_token",
+                "message": diagnostic.message(),
+                "file": "main.denvl",
+                "start": { "line": 2, "column": 3, "offset": 13 },
+                "end": { "line": 2, "column": 3, "offset": 13 },
+                "expected": ["CloseParen"],
+                "actual": null,
+                "suggestions": [{
+                    "description": "insert ')'",
+                    "applicability": "machine-applicable",
+                    "start": { "line": 2, "column": 3, "offset": 13 },
+                    "end": { "line": 2, "column": 3, "offset": 13 },
+                    "text": ")",
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_crlf_offset() {
+        // 元のファイルの "\r\n" は Source では '\n' になるが、offset は元のファイルで数える
+        let source = Source::from_str("let a = 1;\r\n(a\r\n");
+        let diagnostic = missed_token_error(Position(13), vec![TokenKind::CloseParen]);
+        let value = to_value("main.denvl", &source, &diagnostic);
+        assert_eq!(
+            value["start"],
+            json!({ "line": 2, "column": 3, "offset": 14 })
+        );
+        assert_eq!(value["suggestions"][0]["end"]["offset"], 14);
+
+        // 末尾に補った改行は元のファイルの末尾を指す
+        let source = Source::from_str("let a = 1;\r\n(a");
+        let diagnostic = missed_token_error(Position(14), vec![TokenKind::CloseParen]);
+        let value = to_value("main.denvl", &source, &diagnostic);
+        assert_eq!(value["start"]["offset"], 14);
+    }
+
+    #[test]
+    fn test_unexpected_token() {
+        let source = Source::from_str("@");
+        let diagnostic = unexpected_token_error(
+            source.range(),
+            vec![TokenKind::Let, TokenKind::Number],
+            TokenKind::Error,
+        );
+        let value = to_value("main.denvl", &source, &diagnostic);
+        assert_eq!(value["code"], "E0001");
+        assert_eq!(value["expected"], json!(["Let", "Number"]));
+        assert_eq!(value["actual"], "Error");
+    }
+
+    #[test]
+    fn test_one_object_per_line() {
+        let source = Source::from_str("@\n@");
+        let diagnostic = unknown_token_error(Range {
+            start: Position(0),
+            end: Position(1),
+        });
+        let line = render("main.denvl", &source, &diagnostic);
+        assert!(!line.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&line).unwrap()["code"],
+            "E0003"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use super::{Diagnostic, DiagnosticError};
+use crate::source::Range;
+use std::collections::VecDeque;
+
+// 表示する前に診断情報を整理する
+// - 同じ診断や、隣接・重複する unknown token をまとめる
+// - 既に報告した範囲の中や直後で起きた連鎖的なエラーを取り除く
+//   e.g. "let a = @; a" では '@' を報告すれば、初期化式が無いことは報告しなくてよい
+pub fn postprocess(diagnostics: VecDeque<Diagnostic>) -> VecDeque<Diagnostic> {
+    let mut diagnostics: Vec<_> = diagnostics.into_iter().collect();
+    // 同じ位置から始まる診断は広い範囲を持つものを先に置く
+    diagnostics.sort_by_key(|diagnostic| {
+        let range = diagnostic.range();
+        (range.start, std::cmp::Reverse(range.end))
+    });
+
+    let mut result: VecDeque<Diagnostic> = VecDeque::new();
+    // 報告済みのトークンの範囲
+    let mut reported_range: Option<Range> = None;
+    for diagnostic in diagnostics {
+        let range = diagnostic.range();
+        if result.back() == Some(&diagnostic) {
+            continue;
+        }
+        if let Some(reported_range) = reported_range {
+            if is_cascade(reported_range, range) {
+                continue;
+            }
+        }
+
+        if let (
+            Some(Diagnostic::Error(DiagnosticError::UnknownToken { range: last_range })),
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. }),
+        ) = (result.back_mut(), &diagnostic)
+        {
+            if range.start <= last_range.end {
+                last_range.end = last_range.end.max(range.end);
+                reported_range = Some(*last_range);
+                continue;
+            }
+        }
+
+        if !range.is_empty() {
+            reported_range = Some(match reported_range {
+                Some(reported_range) if range.start <= reported_range.end => Range {
+                    start: reported_range.start,
+                    end: reported_range.end.max(range.end),
+                },
+                _ => range,
+            });
+        }
+        result.push_back(diagnostic);
+    }
+    result
+}
+
+// 報告済みの範囲に含まれる診断と、その直後で抜けを報告する診断は連鎖的なエラーとみなす
+fn is_cascade(reported_range: Range, range: Range) -> bool {
+    if range.is_empty() {
+        reported_range.start <= range.start && range.start <= reported_range.end
+    } else {
+        reported_range.start <= range.start && range.end <= reported_range.end
+    }
+}
+
+// 先頭から max_errors 個だけ残し、省略した診断の数を返す
+pub fn limit(diagnostics: &mut VecDeque<Diagnostic>, max_errors: Option<usize>) -> usize {
+    match max_errors {
+        Some(max_errors) if max_errors < diagnostics.len() => {
+            let omitted_count = diagnostics.len() - max_errors;
+            diagnostics.truncate(max_errors);
+            omitted_count
+        }
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{limit, postprocess};
+    use crate::diagnostic::{unknown_token_error, Diagnostic, DiagnosticError};
+    use crate::parse::parse;
+    use crate::source::{Position, Range, Source};
+    use std::collections::VecDeque;
+
+    fn names(src: &str) -> Vec<&'static str> {
+        let source = Source::from_str(src);
+        let (_, diagnostics) = parse(&source);
+        postprocess(diagnostics)
+            .iter()
+            .map(|diagnostic| diagnostic.name())
+            .collect()
+    }
+
+    #[test]
+    fn test_cascade() {
+        assert_eq!(names("let a = @; a"), vec!["unknown_token"]);
+        assert_eq!(names("let @@ = 1; a"), vec!["unknown_token"]);
+        assert_eq!(names("1 @ 2"), vec!["unknown_token"]);
+        assert_eq!(names("@"), vec!["unknown_token"]);
+        // 報告済みの範囲から離れた診断は残す
+        assert_eq!(
+            names("let a = @; (1 + "),
+            vec!["unknown_token", "missing_expression", "missed_token"]
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let range = |start, end| Range {
+            start: Position(start),
+            end: Position(end),
+        };
+        let diagnostics: VecDeque<_> = vec![
+            unknown_token_error(range(0, 2)),
+            unknown_token_error(range(0, 2)),
+            unknown_token_error(range(2, 4)),
+        ]
+        .into_iter()
+        .collect();
+        let diagnostics = postprocess(diagnostics);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0],
+            Diagnostic::Error(DiagnosticError::UnknownToken { range: range(0, 4) })
+        );
+    }
+
+    #[test]
+    fn test_limit() {
+        let source = Source::from_str("let a = ;\nlet b = ;\nlet c = ;\n1");
+        let (_, diagnostics) = parse(&source);
+        let mut diagnostics = postprocess(diagnostics);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(limit(&mut diagnostics, Some(2)), 1);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(limit(&mut diagnostics, None), 0);
+        assert_eq!(diagnostics.len(), 2);
+    }
+}
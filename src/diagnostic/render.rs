@@ -0,0 +1,371 @@
+use super::{Diagnostic, Label};
+use crate::source::Source;
+
+// 診断情報を rustc 風の表示に変換する
+// e.g.
+// error[E0003]: unknown token
+//  --> main.denvl:1:5
+//   |
+// 1 | let @@ = 1
+//   |     ^~ not a valid token
+//   |
+//   = help: remove the unrecognized characters
+pub fn render(
+    filename: &str,
+    source: &Source,
+    diagnostic: &Diagnostic,
+    color: bool,
+) -> Vec<String> {
+    let painter = Painter { color };
+    let severity_style = match diagnostic {
+        Diagnostic::Error(..) => RED,
+        Diagnostic::Warning(..) => YELLOW,
+    };
+
+    let primary = diagnostic.primary_label();
+    let (line, column) = source.line_column(primary.range.start);
+    let mut labels = vec![(underlines(source, &primary, true), primary, severity_style)];
+    for label in diagnostic.secondary_labels() {
+        labels.push((underlines(source, &label, false), label, BLUE));
+    }
+
+    let mut line_numbers: Vec<usize> = labels
+        .iter()
+        .flat_map(|(underlines, ..)| underlines.iter().map(|underline| underline.line))
+        .collect();
+    line_numbers.sort();
+    line_numbers.dedup();
+    let gutter_width = line_numbers.last().unwrap_or(&line).to_string().len();
+    let gutter = |text: &str| painter.paint(BLUE, &format!("{text:>gutter_width$} |"));
+
+    let mut lines = vec![
+        format!(
+            "{}{}",
+            painter.paint(
+                severity_style,
+                &format!("{}[{}]", diagnostic.severity(), diagnostic.code())
+            ),
+            painter.paint(BOLD, &format!(": {}", diagnostic.message()))
+        ),
+        format!(
+            "{}{} {}:{}:{}",
+            " ".repeat(gutter_width),
+            painter.paint(BLUE, "-->"),
+            filename,
+            line,
+            column
+        ),
+        gutter(""),
+    ];
+
+    let mut prev_line_number = None;
+    for line_number in line_numbers {
+        if matches!(prev_line_number, Some(prev) if prev + 1 < line_number) {
+            lines.push(painter.paint(BLUE, "..."));
+        }
+        prev_line_number = Some(line_number);
+
+        let text: String = source.get(&source.line_range(line_number)).iter().collect();
+        if text.is_empty() {
+            lines.push(gutter(&line_number.to_string()));
+        } else {
+            lines.push(format!("{} {}", gutter(&line_number.to_string()), text));
+        }
+
+        for (underlines, label, style) in &labels {
+            let Some(index) = underlines.iter().position(|u| u.line == line_number) else {
+                continue;
+            };
+            let underline = &underlines[index];
+            let mut row = format!(
+                "{} {}{}",
+                gutter(""),
+                underline.padding,
+                painter.paint(style, &underline.marks)
+            );
+            // 説明は範囲の最終行に付ける
+            if index + 1 == underlines.len() && !label.message.is_empty() {
+                row += &format!(" {}", painter.paint(style, &label.message));
+            }
+            lines.push(row);
+        }
+    }
+
+    let notes = diagnostic.notes();
+    let helps = diagnostic.helps();
+    if !notes.is_empty() || !helps.is_empty() {
+        lines.push(gutter(""));
+    }
+    for (kind, message) in notes
+        .iter()
+        .map(|note| ("note", note))
+        .chain(helps.iter().map(|help| ("help", help)))
+    {
+        lines.push(format!(
+            "{} {} {}: {}",
+            " ".repeat(gutter_width),
+            painter.paint(BLUE, "="),
+            painter.paint(BOLD, kind),
+            message
+        ));
+    }
+    lines
+}
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+struct Painter {
+    color: bool,
+}
+
+impl Painter {
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+// 1 行分の下線
+struct Underline {
+    line: usize,
+    padding: String,
+    marks: String,
+}
+
+// label の範囲に引く下線を行ごとに返す
+// 主となる範囲は '^' から始めて '~' で、それ以外の範囲は '-' で下線を引く
+// 幅 0 の範囲でも先頭行には印を一つ付ける
+fn underlines(source: &Source, label: &Label, is_primary: bool) -> Vec<Underline> {
+    let (start_line, start_column) = source.line_column(label.range.start);
+    let (end_line, end_column) = source.line_column(label.range.end);
+
+    let mut result = vec![];
+    for line in start_line..=end_line {
+        // 範囲の終端が行頭にある場合、その行は範囲に含まれない
+        if line != start_line && line == end_line && end_column == 1 {
+            break;
+        }
+        let text = source.get(&source.line_range(line));
+        let from = if line == start_line { start_column } else { 1 };
+        let to = if line == end_line {
+            end_column
+        } else {
+            text.len() + 1
+        };
+        let width = if line == start_line {
+            (to - from).max(1)
+        } else {
+            to - from
+        };
+        if width == 0 {
+            continue;
+        }
+
+        // タブ文字はタブのまま残して表示位置を揃える
+        let padding = text
+            .iter()
+            .take(from - 1)
+            .map(|c| if *c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let marks = (0..width)
+            .map(|i| match (is_primary, line == start_line && i == 0) {
+                (true, true) => '^',
+                (true, false) => '~',
+                (false, _) => '-',
+            })
+            .collect();
+        result.push(Underline {
+            line,
+            padding,
+            marks,
+        });
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::render;
+    use crate::diagnostic::{
+        extra_token_error, missed_token_error, missed_token_error_with_context,
+        unknown_token_error, Context, ContextKind,
+    };
+    use crate::source::{Position, Range, Source};
+    use crate::syntax_node::TokenKind;
+
+    fn range(start: usize, end: usize) -> Range {
+        Range {
+            start: Position(start),
+            end: Position(end),
+        }
+    }
+
+    #[test]
+    fn test_single_line() {
+        let source = Source::from_str("let @@ = 1");
+        let lines = render(
+            "main.denvl",
+            &source,
+            &unknown_token_error(range(4, 6)),
+            false,
+        );
+        assert_eq!(
+            lines,
+            vec![
+                "error[E0003]: unknown token",
+                " --> main.denvl:1:5",
+                "  |",
+                "1 | let @@ = 1",
+                "  |     ^~ not a valid token",
+                "  |",
+                "  = help: remove the unrecognized characters",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_and_column_are_one_origin() {
+        let source = Source::from_str("let a = 1;\n  a @");
+        let lines = render(
+            "main.denvl",
+            &source,
+            &unknown_token_error(range(15, 16)),
+            false,
+        );
+        assert_eq!(lines[1], " --> main.denvl:2:5");
+        assert_eq!(lines[3..5], ["2 |   a @", "  |     ^ not a valid token"]);
+    }
+
+    #[test]
+    fn test_multi_line() {
+        let source = Source::from_str("1 + (2\n* 3)\n+ 4");
+        let lines = render(
+            "main.denvl",
+            &source,
+            &extra_token_error(range(4, 11), TokenKind::OpenParen),
+            false,
+        );
+        assert_eq!(
+            lines[3..7],
+            [
+                "1 | 1 + (2",
+                "  |     ^~",
+                "2 | * 3)",
+                "  | ~~~~ not expected here",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missed_token_at_end_of_file() {
+        let source = Source::from_str("(1");
+        let lines = render(
+            "main.denvl",
+            &source,
+            &missed_token_error(source.range().end, vec![TokenKind::CloseParen]),
+            false,
+        );
+        assert_eq!(lines[0], "error[E0002]: expected ')'");
+        assert_eq!(lines[1], " --> main.denvl:1:3");
+        assert_eq!(
+            lines[3..],
+            [
+                "1 | (1",
+                "  |   ^ expected ')'",
+                "  |",
+                "  = help: insert ')'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_secondary_label() {
+        let source = Source::from_str("(1\n\n\n+ 2");
+        let lines = render(
+            "main.denvl",
+            &source,
+            &missed_token_error_with_context(
+                source.range().end,
+                vec![TokenKind::CloseParen],
+                Context {
+                    kind: ContextKind::Paren,
+                    range: range(0, 1),
+                },
+            ),
+            false,
+        );
+        assert_eq!(
+            lines[3..8],
+            [
+                "1 | (1",
+                "  | - unclosed delimiter",
+                "...",
+                "4 | + 2",
+                "  |    ^ expected ')'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_note() {
+        let source = Source::from_str("1 +");
+        let lines = render(
+            "main.denvl",
+            &source,
+            &missed_token_error(
+                source.range().end,
+                vec![
+                    TokenKind::Ident,
+                    TokenKind::Number,
+                    TokenKind::True,
+                    TokenKind::False,
+                    TokenKind::OpenParen,
+                ],
+            ),
+            false,
+        );
+        assert_eq!(lines[0], "error[E0002]: expected an expression");
+        assert_eq!(
+            lines[lines.len() - 1],
+            "  = note: an expression starts with one of identifier, number, 'true', 'false', 'let', 'if', 'fun', '(', '-', '+' or '!'"
+        );
+    }
+
+    #[test]
+    fn test_tab_is_kept_for_alignment() {
+        let source = Source::from_str("\t@");
+        let lines = render(
+            "main.denvl",
+            &source,
+            &unknown_token_error(range(1, 2)),
+            false,
+        );
+        assert_eq!(lines[3..5], ["1 | \t@", "  | \t^ not a valid token"]);
+    }
+
+    #[test]
+    fn test_color() {
+        let source = Source::from_str("@");
+        let lines = render(
+            "main.denvl",
+            &source,
+            &unknown_token_error(range(0, 1)),
+            true,
+        );
+        assert_eq!(
+            lines[0],
+            "\x1b[1;31merror[E0003]\x1b[0m\x1b[1m: unknown token\x1b[0m"
+        );
+        assert_eq!(
+            lines[4],
+            "\x1b[1;34m  |\x1b[0m \x1b[1;31m^\x1b[0m \x1b[1;31mnot a valid token\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use super::code::DIAGNOSTIC_CODES;
+use super::Diagnostic;
+use crate::source::Source;
+use serde_json::{json, Value};
+use std::path::Path;
+
+// コードレビューシステムに渡すための SARIF 2.1.0 形式のログ
+// 構文解析やそれ以降の解析で集めた診断情報をまとめて 1 つの run として出力する
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+pub fn render<'a>(
+    filename: &str,
+    source: &Source,
+    diagnostics: impl IntoIterator<Item = &'a Diagnostic>,
+) -> String {
+    to_value(filename, source, diagnostics).to_string()
+}
+
+pub fn to_value<'a>(
+    filename: &str,
+    source: &Source,
+    diagnostics: impl IntoIterator<Item = &'a Diagnostic>,
+) -> Value {
+    let rules: Vec<Value> = DIAGNOSTIC_CODES
+        .iter()
+        .map(|code| {
+            json!({
+                "id": code.code,
+                "name": code.name,
+                "shortDescription": { "text": code.summary },
+                "help": { "text": code.explanation },
+                "defaultConfiguration": { "level": "error" },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = diagnostics
+        .into_iter()
+        .map(|diagnostic| result(filename, source, diagnostic))
+        .collect();
+
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            // 列番号は UTF-16 ではなく文字単位で数えている
+            "columnKind": "unicodeCodePoints",
+            "artifacts": [{ "location": { "uri": to_uri(filename) } }],
+            "results": results,
+        }],
+    })
+}
+
+fn result(filename: &str, source: &Source, diagnostic: &Diagnostic) -> Value {
+    let range = diagnostic.range();
+    let rule_index = DIAGNOSTIC_CODES
+        .iter()
+        .position(|code| code.code == diagnostic.code())
+        .expect("every diagnostic code is registered");
+    let (start_line, start_column) = source.line_column(range.start);
+    let (end_line, end_column) = source.line_column(range.end);
+    // charOffset は元のファイル先頭からの文字数なので、"\r\n" の '\r' も数える
+    let char_offset = source.original_offset(range.start);
+    let char_end = source.original_offset(range.end);
+    json!({
+        "ruleId": diagnostic.code(),
+        "ruleIndex": rule_index,
+        "level": diagnostic.severity(),
+        "message": { "text": diagnostic.make_msg() },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": to_uri(filename), "index": 0 },
+                "region": {
+                    "startLine": start_line,
+                    "startColumn": start_column,
+                    "endLine": end_line,
+                    "endColumn": end_column,
+                    "charOffset": char_offset,
+                    "charLength": char_end - char_offset,
+                },
+            },
+        }],
+    })
+}
+
+// SARIF の uri は URI 参照でなければならない
+// 絶対パスは file URI に、相対パスはパーセントエンコードした相対参照にする
+fn to_uri(filename: &str) -> String {
+    let path = filename.replace(std::path::MAIN_SEPARATOR, "/");
+    if Path::new(filename).is_absolute() {
+        // Windows のパス (e.g. C:/a.denvl) は '/' で始まらない
+        let path = if path.starts_with('/') {
+            path
+        } else {
+            format!("/{path}")
+        };
+        format!("file://{}", percent_encode(&path, ":"))
+    } else {
+        // 相対参照の先頭の区間に ':' があるとスキームと区別できないので、':' もエンコードする
+        percent_encode(&path, "")
+    }
+}
+
+// 非予約文字と '/'、allowed に含まれる文字以外を UTF-8 のバイトごとに %XX で表す
+fn percent_encode(path: &str, allowed: &str) -> String {
+    let mut result = String::new();
+    for c in path.chars() {
+        if c.is_ascii_alphanumeric() || "-._~/".contains(c) || allowed.contains(c) {
+            result.push(c);
+        } else {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                result += &format!("%{byte:02X}");
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::{to_uri, to_value};
+    use crate::diagnostic::code::DIAGNOSTIC_CODES;
+    use crate::diagnostic::unknown_token_error;
+    use crate::source::{Position, Range, Source};
+    use serde_json::json;
+
+    fn range(start: usize, end: usize) -> Range {
+        Range {
+            start: Position(start),
+            end: Position(end),
+        }
+    }
+
+    #[test]
+    fn test_log() {
+        let source = Source::from_str("let a = 1;\na @");
+        let diagnostics = vec![unknown_token_error(range(13, 14))];
+        let log = to_value("main.denvl", &source, &diagnostics);
+
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "denvl");
+        assert_eq!(
+            run["tool"]["driver"]["rules"].as_array().unwrap().len(),
+            DIAGNOSTIC_CODES.len()
+        );
+        assert_eq!(run["artifacts"][0]["location"]["uri"], "main.denvl");
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "E0003");
+        assert_eq!(result["ruleIndex"], 2);
+        assert_eq!(result["level"], "error");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"],
+            json!({
+                "startLine": 2,
+                "startColumn": 3,
+                "endLine": 2,
+                "endColumn": 4,
+                "charOffset": 13,
+                "charLength": 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_crlf_char_offset() {
+        let source = Source::from_str("let a = 1;\r\na @\r\n");
+        let diagnostics = vec![unknown_token_error(range(13, 14))];
+        let log = to_value("main.denvl", &source, &diagnostics);
+        let region = &log["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 2);
+        assert_eq!(region["startColumn"], 3);
+        assert_eq!(region["charOffset"], 14);
+        assert_eq!(region["charLength"], 1);
+    }
+
+    #[test]
+    fn test_empty_log() {
+        let source = Source::from_str("1");
+        let log = to_value("main.denvl", &source, &vec![]);
+        assert_eq!(log["runs"][0]["results"], json!([]));
+    }
+
+    #[test]
+    fn test_uri() {
+        assert_eq!(to_uri("main.denvl"), "main.denvl");
+        assert_eq!(
+            to_uri("my dir/50%#1:ü.denvl"),
+            "my%20dir/50%25%231%3A%C3%BC.denvl"
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            to_uri("/home/a b/main.denvl"),
+            "file:///home/a%20b/main.denvl"
+        );
+        #[cfg(windows)]
+        assert_eq!(to_uri("C:\\a b\\main.denvl"), "file:///C:/a%20b/main.denvl");
+    }
+}
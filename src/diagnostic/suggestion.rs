@@ -0,0 +1,240 @@
+use crate::source::{Position, Range, Source};
+
+// 診断に付ける修正案
+// e.g. ';' の抜けに対して、抜けている位置に ';' を挿入する
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    pub edit: TextEdit,
+    // "insert ';'" のように help として表示する説明
+    pub description: String,
+    pub applicability: Applicability,
+}
+
+// range を text で置き換える。挿入は幅 0 の range、削除は空の text で表す
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range,
+    pub text: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Applicability {
+    // そのまま適用してよい。`denvl fix` はこれだけを適用する
+    MachineApplicable,
+    // 利用者の意図と違うかもしれない (e.g. 抜けている演算子を '+' とみなす)
+    MaybeIncorrect,
+}
+
+impl Applicability {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "machine-applicable",
+            Applicability::MaybeIncorrect => "maybe-incorrect",
+        }
+    }
+}
+
+impl Suggestion {
+    pub fn insert(pos: Position, text: &str, applicability: Applicability) -> Self {
+        Suggestion {
+            edit: TextEdit {
+                range: Range {
+                    start: pos,
+                    end: pos,
+                },
+                text: text.to_string(),
+            },
+            description: format!("insert '{}'", text.trim()),
+            applicability,
+        }
+    }
+
+    pub fn remove(range: Range, description: &str, applicability: Applicability) -> Self {
+        Suggestion {
+            edit: TextEdit {
+                range,
+                text: String::new(),
+            },
+            description: description.to_string(),
+            applicability,
+        }
+    }
+}
+
+// 機械的に適用できる修正案を全て適用したソースコードと、適用した修正案の数を返す
+// 範囲が重なる修正案は先に現れたものだけを適用する
+pub fn apply<'a>(
+    source: &Source,
+    suggestions: impl IntoIterator<Item = &'a Suggestion>,
+) -> (String, usize) {
+    let edits = applicable_edits(suggestions);
+    let mut result = String::new();
+    let mut pos = source.range().start;
+    for edit in &edits {
+        result.extend(source.get(&Range {
+            start: pos,
+            end: edit.range.start,
+        }));
+        result.push_str(&edit.text);
+        pos = edit.range.end;
+    }
+    result.extend(source.get(&Range {
+        start: pos,
+        end: source.range().end,
+    }));
+    (result, edits.len())
+}
+
+// apply と同じだが、Source に読み込む前の文字列 original に修正案を適用する
+// Source は改行を '\n' にそろえて末尾に改行を補うので、修正案の及ばない部分は original のまま残す
+pub fn apply_to_original<'a>(
+    original: &str,
+    suggestions: impl IntoIterator<Item = &'a Suggestion>,
+) -> (String, usize) {
+    let offsets = original_offsets(original);
+    let edits = applicable_edits(suggestions);
+    let mut result = String::new();
+    let mut offset = 0;
+    for edit in &edits {
+        result.push_str(&original[offset..offsets[edit.range.start.0]]);
+        result.push_str(&edit.text);
+        offset = offsets[edit.range.end.0];
+    }
+    result.push_str(&original[offset..]);
+    (result, edits.len())
+}
+
+// 適用する修正を位置の順に並べる。範囲が重なるものは先に現れたものだけを残す
+fn applicable_edits<'a>(
+    suggestions: impl IntoIterator<Item = &'a Suggestion>,
+) -> Vec<&'a TextEdit> {
+    let mut edits: Vec<&TextEdit> = suggestions
+        .into_iter()
+        .filter(|suggestion| suggestion.applicability == Applicability::MachineApplicable)
+        .map(|suggestion| &suggestion.edit)
+        .collect();
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+    // 同じ修正案が複数の診断から提案されることがある
+    edits.dedup();
+
+    let mut result = Vec::new();
+    let mut pos = Position::start();
+    for edit in edits {
+        if edit.range.start < pos {
+            continue;
+        }
+        pos = edit.range.end;
+        result.push(edit);
+    }
+    result
+}
+
+// Source::from_str(original) の各位置 (と末尾) に対応する original のバイト位置
+// 行末の '\n' は元の改行 ("\r\n" なら '\r') の位置に、補った改行は original の末尾に対応させる
+fn original_offsets(original: &str) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut line_start = 0;
+    for line in original.lines() {
+        offsets.extend(line.char_indices().map(|(i, _)| line_start + i));
+        let line_end = line_start + line.len();
+        offsets.push(line_end);
+        line_start = match original[line_end..].find('\n') {
+            Some(i) => line_end + i + 1,
+            None => original.len(),
+        };
+    }
+    offsets.push(original.len());
+    offsets
+}
+
+#[cfg(test)]
+mod test {
+    use super::{apply, apply_to_original, Applicability, Suggestion};
+    use crate::source::{Position, Range, Source};
+
+    fn range(start: usize, end: usize) -> Range {
+        Range {
+            start: Position(start),
+            end: Position(end),
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let source = Source::from_str("let a = 1 a @");
+        let suggestions = vec![
+            Suggestion::remove(range(12, 13), "remove", Applicability::MachineApplicable),
+            Suggestion::insert(Position(9), ";", Applicability::MachineApplicable),
+            // 適用しない
+            Suggestion::insert(Position(10), "+ ", Applicability::MaybeIncorrect),
+        ];
+        assert_eq!(
+            apply(&source, &suggestions),
+            ("let a = 1; a \n".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn test_apply_overlapping() {
+        let source = Source::from_str("@@ 1");
+        let suggestions = vec![
+            Suggestion::remove(range(0, 2), "remove", Applicability::MachineApplicable),
+            Suggestion::remove(range(1, 2), "remove", Applicability::MachineApplicable),
+        ];
+        assert_eq!(apply(&source, &suggestions), (" 1\n".to_string(), 1));
+    }
+
+    #[test]
+    fn test_apply_to_original_keeps_line_endings() {
+        let suggestions = vec![Suggestion::insert(
+            Position(9),
+            ";",
+            Applicability::MachineApplicable,
+        )];
+        assert_eq!(
+            apply_to_original("let a = 1\r\na\r\n", &suggestions),
+            ("let a = 1;\r\na\r\n".to_string(), 1)
+        );
+        // 最終行に改行が無ければ補わない
+        assert_eq!(
+            apply_to_original("let a = 1\na", &suggestions),
+            ("let a = 1;\na".to_string(), 1)
+        );
+        assert_eq!(
+            apply_to_original("let a = 1\r\na", &[]),
+            ("let a = 1\r\na".to_string(), 0)
+        );
+        // 行末の改行を含む範囲は元の改行ごと置き換える
+        let suggestions = vec![
+            Suggestion::remove(range(1, 3), "remove", Applicability::MachineApplicable),
+            Suggestion::insert(Position(4), "+", Applicability::MachineApplicable),
+        ];
+        assert_eq!(
+            apply_to_original("a@\r\nb\r\n", &suggestions),
+            ("ab+\r\n".to_string(), 2)
+        );
+    }
+
+    #[test]
+    fn test_apply_to_original_round_trip() {
+        use crate::parse::parse;
+
+        fn fix(original: &str) -> String {
+            let source = Source::from_str(original);
+            let (_, diagnostics) = parse(&source);
+            let suggestions: Vec<_> = diagnostics
+                .iter()
+                .flat_map(|diagnostic| diagnostic.suggestions())
+                .collect();
+            apply_to_original(original, &suggestions).0
+        }
+
+        // 修正案の及ばない "\r\n" や行末の空白、最終行の改行の有無はそのまま残る
+        assert_eq!(
+            fix("let a = 1  \r\n(a + 2)\r\n\r\n"),
+            "let a = 1;  \r\n(a + 2)\r\n\r\n"
+        );
+        assert_eq!(fix("let a = 1\r\n(a"), "let a = 1;\r\n(a)");
+        assert_eq!(fix("1 +\r\n2\r\n"), "1 +\r\n2\r\n");
+    }
+}
@@ -0,0 +1,592 @@
+use crate::ast::{BinExpr, BinOp, CallExpr, Expr, IfExpr, LambdaExpr, LetExpr, UnaryExpr, UnaryOp};
+use crate::source::{Range, Source};
+use std::fmt;
+use std::rc::Rc;
+
+// 型付きの AST を辿って式を評価する
+// 構文エラーのある構文木も評価できるが、抜けている部分に辿り着いた時点で EvalError::Incomplete を返す
+
+#[derive(Clone, Debug)]
+pub enum Value<'a> {
+    Int(i64),
+    Bool(bool),
+    Closure(Rc<Closure<'a>>),
+}
+
+// 無名関数を評価した値。作られたときの環境を持ち回る
+#[derive(Debug)]
+pub struct Closure<'a> {
+    param: Rc<str>,
+    body: Option<Expr<'a>>,
+    // 本体が欠けていたときに報告する範囲
+    range: Range,
+    vars: Vars<'a>,
+}
+
+impl Value<'_> {
+    // 型の名前。型エラーのメッセージに使う
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "integer",
+            Value::Bool(_) => "boolean",
+            Value::Closure(_) => "function",
+        }
+    }
+}
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{n}"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Closure(_) => write!(f, "<function>"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EvalError {
+    // 構文エラーにより式が欠けている
+    Incomplete {
+        range: Range,
+    },
+    UnboundVariable {
+        name: String,
+        range: Range,
+    },
+    DivisionByZero {
+        range: Range,
+    },
+    // 整数リテラルや演算結果が i64 に収まらない
+    Overflow {
+        range: Range,
+    },
+    // 被演算子の型が演算子に合わない
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+        range: Range,
+    },
+    // 関数呼び出しが深くなりすぎた。e.g. (fun f -> f(f))(fun f -> f(f))
+    StackOverflow {
+        range: Range,
+    },
+}
+
+impl EvalError {
+    pub fn range(&self) -> Range {
+        match self {
+            EvalError::Incomplete { range }
+            | EvalError::UnboundVariable { range, .. }
+            | EvalError::DivisionByZero { range }
+            | EvalError::Overflow { range }
+            | EvalError::TypeMismatch { range, .. }
+            | EvalError::StackOverflow { range } => *range,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            EvalError::Incomplete { .. } => "cannot evaluate incomplete expression".to_string(),
+            EvalError::UnboundVariable { name, .. } => format!("unbound variable `{name}`"),
+            EvalError::DivisionByZero { .. } => "division by zero".to_string(),
+            EvalError::Overflow { .. } => "integer overflow".to_string(),
+            EvalError::TypeMismatch {
+                expected, found, ..
+            } => format!("type mismatch: expected {expected}, found {found}"),
+            EvalError::StackOverflow { .. } => "too deeply nested function calls".to_string(),
+        }
+    }
+}
+
+// 評価中の関数呼び出しの深さの上限。これを超えるとネイティブのスタックを使い切るおそれがある
+const MAX_CALL_DEPTH: usize = 1000;
+
+struct Env<'a> {
+    vars: Vars<'a>,
+    call_depth: usize,
+}
+
+// 変数の束縛を内側から外側へ辿る連結リスト
+// 外側の束縛は Rc で共有するので、関数が環境を捕まえるときや呼び出すときに複製しない
+#[derive(Clone, Debug, Default)]
+struct Vars<'a>(Option<Rc<Binding<'a>>>);
+
+#[derive(Debug)]
+struct Binding<'a> {
+    name: Rc<str>,
+    value: Value<'a>,
+    outer: Vars<'a>,
+}
+
+impl<'a> Vars<'a> {
+    fn bind(&self, name: Rc<str>, value: Value<'a>) -> Self {
+        Vars(Some(Rc::new(Binding {
+            name,
+            value,
+            outer: self.clone(),
+        })))
+    }
+
+    // 内側の束縛が外側の束縛を隠す
+    fn lookup(&self, name: &str) -> Option<&Value<'a>> {
+        let mut vars = self;
+        while let Some(binding) = &vars.0 {
+            if *binding.name == *name {
+                return Some(&binding.value);
+            }
+            vars = &binding.outer;
+        }
+        None
+    }
+}
+
+pub fn eval<'a>(source: &Source, expr: Option<Expr<'a>>) -> Result<Value<'a>, EvalError> {
+    let mut env = Env {
+        vars: Vars::default(),
+        call_depth: 0,
+    };
+    eval_expr(source, &mut env, expr, source.range())
+}
+
+// expr が欠けていたときは outer_range を報告する
+fn eval_expr<'a>(
+    source: &Source,
+    env: &mut Env<'a>,
+    expr: Option<Expr<'a>>,
+    outer_range: Range,
+) -> Result<Value<'a>, EvalError> {
+    let Some(expr) = expr else {
+        return Err(EvalError::Incomplete { range: outer_range });
+    };
+    match expr {
+        Expr::Int(int_expr) => match int_expr.value(source) {
+            Some(n) => Ok(Value::Int(n)),
+            None => Err(EvalError::Overflow {
+                range: int_expr.text_range(),
+            }),
+        },
+        Expr::Bool(bool_expr) => Ok(Value::Bool(bool_expr.value())),
+        Expr::Var(var_expr) => {
+            let name = var_expr.name();
+            let text: String = name.text(source).iter().collect();
+            match env.vars.lookup(&text) {
+                Some(value) => Ok(value.clone()),
+                None => Err(EvalError::UnboundVariable {
+                    name: text,
+                    range: name.text_range(),
+                }),
+            }
+        }
+        Expr::Let(let_expr) => eval_let(source, env, let_expr, expr.range()),
+        Expr::If(if_expr) => eval_if(source, env, if_expr, expr.range()),
+        Expr::Bin(bin_expr) => eval_bin(source, env, bin_expr, expr.range()),
+        Expr::Unary(unary_expr) => eval_unary(source, env, unary_expr, expr.range()),
+        Expr::Paren(paren_expr) => eval_expr(source, env, paren_expr.inner(), expr.range()),
+        Expr::Lambda(lambda_expr) => eval_lambda(source, env, lambda_expr, expr.range()),
+        Expr::Call(call_expr) => eval_call(source, env, call_expr, expr.range()),
+    }
+}
+
+fn eval_let<'a>(
+    source: &Source,
+    env: &mut Env<'a>,
+    let_expr: LetExpr<'a>,
+    range: Range,
+) -> Result<Value<'a>, EvalError> {
+    let Some(name) = let_expr.name() else {
+        return Err(EvalError::Incomplete { range });
+    };
+    let value = eval_expr(source, env, let_expr.init(), range)?;
+    let name: String = name.text(source).iter().collect();
+    let inner = env.vars.bind(name.into(), value);
+    let outer = std::mem::replace(&mut env.vars, inner);
+    let result = eval_expr(source, env, let_expr.body(), range);
+    env.vars = outer;
+    result
+}
+
+// 選ばれなかった節は評価しない
+fn eval_if<'a>(
+    source: &Source,
+    env: &mut Env<'a>,
+    if_expr: IfExpr<'a>,
+    range: Range,
+) -> Result<Value<'a>, EvalError> {
+    if eval_bool(source, env, if_expr.cond(), range)? {
+        eval_expr(source, env, if_expr.then_branch(), range)
+    } else {
+        eval_expr(source, env, if_expr.else_branch(), range)
+    }
+}
+
+// 整数に評価されるはずの式を評価する
+fn eval_int<'a>(
+    source: &Source,
+    env: &mut Env<'a>,
+    expr: Option<Expr<'a>>,
+    outer_range: Range,
+) -> Result<i64, EvalError> {
+    match eval_expr(source, env, expr, outer_range)? {
+        Value::Int(n) => Ok(n),
+        value => Err(type_mismatch("integer", &value, expr, outer_range)),
+    }
+}
+
+// 真偽値に評価されるはずの式を評価する
+fn eval_bool<'a>(
+    source: &Source,
+    env: &mut Env<'a>,
+    expr: Option<Expr<'a>>,
+    outer_range: Range,
+) -> Result<bool, EvalError> {
+    match eval_expr(source, env, expr, outer_range)? {
+        Value::Bool(b) => Ok(b),
+        value => Err(type_mismatch("boolean", &value, expr, outer_range)),
+    }
+}
+
+// 型が合わなかった式 expr の範囲を報告する
+fn type_mismatch(
+    expected: &'static str,
+    found: &Value,
+    expr: Option<Expr>,
+    outer_range: Range,
+) -> EvalError {
+    EvalError::TypeMismatch {
+        expected,
+        found: found.type_name(),
+        range: expr.map_or(outer_range, |expr| expr.range()),
+    }
+}
+
+fn eval_bin<'a>(
+    source: &Source,
+    env: &mut Env<'a>,
+    bin_expr: BinExpr<'a>,
+    range: Range,
+) -> Result<Value<'a>, EvalError> {
+    let op = bin_expr.op();
+    match op {
+        // 左辺で結果が決まれば右辺は評価しない
+        BinOp::And | BinOp::Or => {
+            let lhs = eval_bool(source, env, bin_expr.lhs(), range)?;
+            if lhs == (op == BinOp::Or) {
+                return Ok(Value::Bool(lhs));
+            }
+            let rhs = eval_bool(source, env, bin_expr.rhs(), range)?;
+            return Ok(Value::Bool(rhs));
+        }
+        // 同じ型どうしなら比べられる。ただし関数は比べられない
+        BinOp::Eq | BinOp::Ne => {
+            let lhs = eval_expr(source, env, bin_expr.lhs(), range)?;
+            let rhs = eval_expr(source, env, bin_expr.rhs(), range)?;
+            let equal = match (&lhs, &rhs) {
+                (Value::Int(lhs), Value::Int(rhs)) => lhs == rhs,
+                (Value::Bool(lhs), Value::Bool(rhs)) => lhs == rhs,
+                (Value::Closure(_), _) => {
+                    return Err(type_mismatch(
+                        "integer or boolean",
+                        &lhs,
+                        bin_expr.lhs(),
+                        range,
+                    ))
+                }
+                (_, Value::Closure(_)) => {
+                    return Err(type_mismatch(
+                        "integer or boolean",
+                        &rhs,
+                        bin_expr.rhs(),
+                        range,
+                    ))
+                }
+                _ => return Err(type_mismatch(lhs.type_name(), &rhs, bin_expr.rhs(), range)),
+            };
+            return Ok(Value::Bool(equal == (op == BinOp::Eq)));
+        }
+        _ => {}
+    }
+
+    let lhs = eval_int(source, env, bin_expr.lhs(), range)?;
+    let rhs = eval_int(source, env, bin_expr.rhs(), range)?;
+    let result = match op {
+        BinOp::Add => lhs.checked_add(rhs),
+        BinOp::Sub => lhs.checked_sub(rhs),
+        BinOp::Mul => lhs.checked_mul(rhs),
+        BinOp::Div if rhs == 0 => return Err(EvalError::DivisionByZero { range }),
+        BinOp::Div => lhs.checked_div(rhs),
+        BinOp::Lt => return Ok(Value::Bool(lhs < rhs)),
+        BinOp::Le => return Ok(Value::Bool(lhs <= rhs)),
+        BinOp::Gt => return Ok(Value::Bool(lhs > rhs)),
+        BinOp::Ge => return Ok(Value::Bool(lhs >= rhs)),
+        BinOp::Eq | BinOp::Ne | BinOp::And | BinOp::Or => unreachable!("handled above"),
+    };
+    result.map(Value::Int).ok_or(EvalError::Overflow { range })
+}
+
+fn eval_unary<'a>(
+    source: &Source,
+    env: &mut Env<'a>,
+    unary_expr: UnaryExpr<'a>,
+    range: Range,
+) -> Result<Value<'a>, EvalError> {
+    let operand_expr = unary_expr.operand();
+    let result = match unary_expr.op() {
+        UnaryOp::Not => return Ok(Value::Bool(!eval_bool(source, env, operand_expr, range)?)),
+        UnaryOp::Neg => eval_int(source, env, operand_expr, range)?.checked_neg(),
+        UnaryOp::Plus => Some(eval_int(source, env, operand_expr, range)?),
+    };
+    result.map(Value::Int).ok_or(EvalError::Overflow { range })
+}
+
+// 本体は呼び出されるまで評価しない
+fn eval_lambda<'a>(
+    source: &Source,
+    env: &mut Env<'a>,
+    lambda_expr: LambdaExpr<'a>,
+    range: Range,
+) -> Result<Value<'a>, EvalError> {
+    let Some(param) = lambda_expr.param() else {
+        return Err(EvalError::Incomplete { range });
+    };
+    Ok(Value::Closure(Rc::new(Closure {
+        param: param.text(source).iter().collect::<String>().into(),
+        body: lambda_expr.body(),
+        range,
+        vars: env.vars.clone(),
+    })))
+}
+
+// 関数の本体は呼び出し側ではなく関数が作られたときの環境で評価する
+fn eval_call<'a>(
+    source: &Source,
+    env: &mut Env<'a>,
+    call_expr: CallExpr<'a>,
+    range: Range,
+) -> Result<Value<'a>, EvalError> {
+    let callee_expr = call_expr.callee();
+    let closure = match eval_expr(source, env, callee_expr, range)? {
+        Value::Closure(closure) => closure,
+        value => return Err(type_mismatch("function", &value, callee_expr, range)),
+    };
+    let arg = eval_expr(source, env, call_expr.arg(), range)?;
+    if env.call_depth >= MAX_CALL_DEPTH {
+        return Err(EvalError::StackOverflow { range });
+    }
+    let mut callee_env = Env {
+        vars: closure.vars.bind(closure.param.clone(), arg),
+        call_depth: env.call_depth + 1,
+    };
+    eval_expr(source, &mut callee_env, closure.body, closure.range)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{eval, EvalError, Value};
+    use crate::ast::Expr;
+    use crate::parse::parse;
+    use crate::source::Source;
+
+    // 評価結果を assert_eq! で比べるため。関数は比べられないので等しいとはしない
+    impl PartialEq for Value<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            match (self, other) {
+                (Value::Int(lhs), Value::Int(rhs)) => lhs == rhs,
+                (Value::Bool(lhs), Value::Bool(rhs)) => lhs == rhs,
+                _ => false,
+            }
+        }
+    }
+
+    // 関数値は構文木を借りていて関数の外へ持ち出せないので、整数と真偽値だけを返す
+    fn eval_str(src: &str) -> Result<Value<'static>, EvalError> {
+        let source = Source::from_str(src);
+        let (node, _) = parse(&source);
+        match eval(&source, Expr::root(&node))? {
+            Value::Int(n) => Ok(Value::Int(n)),
+            Value::Bool(b) => Ok(Value::Bool(b)),
+            Value::Closure(_) => panic!("unexpected function value: {src}"),
+        }
+    }
+
+    fn eval_to_string(src: &str) -> Result<String, EvalError> {
+        let source = Source::from_str(src);
+        let (node, _) = parse(&source);
+        eval(&source, Expr::root(&node)).map(|value| value.to_string())
+    }
+
+    #[test]
+    fn test_eval() {
+        assert_eq!(eval_str("1 + 2 * 3"), Ok(Value::Int(7)));
+        assert_eq!(eval_str("(1 + 2) * 3"), Ok(Value::Int(9)));
+        assert_eq!(eval_str("10 - 4 - 3"), Ok(Value::Int(3)));
+        assert_eq!(
+            eval_str("let a = 12;\nlet b = a + 5;\na + b"),
+            Ok(Value::Int(29))
+        );
+        // 内側の束縛が外側の束縛を隠す
+        assert_eq!(eval_str("let a = 1; let a = a + 1; a"), Ok(Value::Int(2)));
+        assert_eq!(eval_str("-3 * -(1 - 5)"), Ok(Value::Int(-12)));
+        assert_eq!(eval_str("1 - -2 + +3"), Ok(Value::Int(6)));
+    }
+
+    #[test]
+    fn test_eval_deep() {
+        // 深い木でも各ノードの範囲は子ノードが持つ幅から求まる
+        let n = 500;
+        assert_eq!(
+            eval_str(&format!("{}1", "1 + ".repeat(n))),
+            Ok(Value::Int(n as i64 + 1))
+        );
+    }
+
+    #[test]
+    fn test_eval_bool() {
+        assert_eq!(eval_str("true"), Ok(Value::Bool(true)));
+        assert_eq!(eval_str("!true"), Ok(Value::Bool(false)));
+        assert_eq!(eval_str("1 + 2 < 4"), Ok(Value::Bool(true)));
+        assert_eq!(eval_str("2 <= 1 || 3 >= 3"), Ok(Value::Bool(true)));
+        assert_eq!(eval_str("1 == 1 && 2 != 2"), Ok(Value::Bool(false)));
+        assert_eq!(eval_str("true == (1 > 0)"), Ok(Value::Bool(true)));
+        // 左辺で結果が決まれば右辺は評価しない
+        assert_eq!(eval_str("false && 1 / 0 == 0"), Ok(Value::Bool(false)));
+        assert_eq!(eval_str("true || undefined"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_eval_if() {
+        assert_eq!(eval_str("if 1 < 2 then 10 else 20"), Ok(Value::Int(10)));
+        assert_eq!(
+            eval_str("let a = 5;\nif a < 0 then -1 else if a == 0 then 0 else 1"),
+            Ok(Value::Int(1))
+        );
+        assert_eq!(
+            eval_str("let a = if true then 1 else 2; a + 1"),
+            Ok(Value::Int(2))
+        );
+        // 選ばれなかった節は評価しない
+        assert_eq!(eval_str("if false then 1 / 0 else 3"), Ok(Value::Int(3)));
+        assert!(matches!(
+            eval_str("if 1 then 2 else 3"),
+            Err(EvalError::TypeMismatch {
+                expected: "boolean",
+                found: "integer",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_eval_lambda() {
+        assert_eq!(eval_str("(fun x -> x + 1)(2)"), Ok(Value::Int(3)));
+        assert_eq!(
+            eval_str("let inc = fun x -> x + 1;\ninc(inc(1))"),
+            Ok(Value::Int(3))
+        );
+        // 部分適用
+        assert_eq!(
+            eval_str("(fun x -> fun y -> x - y)(5)(3)"),
+            Ok(Value::Int(2))
+        );
+        // 関数は作られたときの環境を覚えている
+        assert_eq!(
+            eval_str("let a = 1;\nlet f = fun x -> x + a;\nlet a = 10;\nf(a)"),
+            Ok(Value::Int(11))
+        );
+        assert_eq!(
+            eval_str("let twice = fun f -> fun x -> f(f(x));\ntwice(fun x -> x * 3)(2)"),
+            Ok(Value::Int(18))
+        );
+        // 本体は呼び出されるまで評価しない
+        assert_eq!(
+            eval_to_string("fun x -> 1 / 0"),
+            Ok("<function>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_eval_lambda_error() {
+        assert!(matches!(
+            eval_str("1(2)"),
+            Err(EvalError::TypeMismatch {
+                expected: "function",
+                found: "integer",
+                ..
+            })
+        ));
+        assert!(matches!(
+            eval_str("(fun x -> x) == (fun x -> x)"),
+            Err(EvalError::TypeMismatch {
+                expected: "integer or boolean",
+                found: "function",
+                ..
+            })
+        ));
+        assert!(matches!(
+            eval_str("1 != (fun x -> x)"),
+            Err(EvalError::TypeMismatch {
+                expected: "integer or boolean",
+                found: "function",
+                ..
+            })
+        ));
+        assert!(matches!(
+            eval_str("(fun x -> y)(1)"),
+            Err(EvalError::UnboundVariable { name, .. }) if name == "y"
+        ));
+        assert!(matches!(
+            eval_str("(fun f -> f(f))(fun f -> f(f))"),
+            Err(EvalError::StackOverflow { .. })
+        ));
+        assert!(matches!(
+            eval_str("(fun -> 1)(2)"),
+            Err(EvalError::Incomplete { .. })
+        ));
+    }
+
+    #[test]
+    fn test_eval_error() {
+        assert!(matches!(
+            eval_str("let a = 1; b"),
+            Err(EvalError::UnboundVariable { name, .. }) if name == "b"
+        ));
+        assert!(matches!(
+            eval_str("1 / (2 - 2)"),
+            Err(EvalError::DivisionByZero { .. })
+        ));
+        assert!(matches!(
+            eval_str("99999999999999999999"),
+            Err(EvalError::Overflow { .. })
+        ));
+        assert!(matches!(eval_str("1 +"), Err(EvalError::Incomplete { .. })));
+        assert!(matches!(eval_str("-"), Err(EvalError::Incomplete { .. })));
+        assert!(matches!(
+            eval_str("1 + true"),
+            Err(EvalError::TypeMismatch {
+                expected: "integer",
+                found: "boolean",
+                ..
+            })
+        ));
+        assert!(matches!(
+            eval_str("!1"),
+            Err(EvalError::TypeMismatch {
+                expected: "boolean",
+                found: "integer",
+                ..
+            })
+        ));
+        assert!(matches!(
+            eval_str("1 == false"),
+            Err(EvalError::TypeMismatch {
+                expected: "integer",
+                found: "boolean",
+                ..
+            })
+        ));
+        assert!(matches!(
+            eval_str("-(-9223372036854775807 - 1)"),
+            Err(EvalError::Overflow { .. })
+        ));
+    }
+}
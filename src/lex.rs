@@ -16,11 +16,17 @@ use number::lex_number;
 
 use self::trivia::trivia_width;
 
+/// [`lex`] の結果
 pub struct LexResult {
     pub token: SyntaxToken,
     pub remaining_range: Range,
 }
 
+/// range の先頭からトークンを 1 つ読む
+///
+/// トークンの後ろの trivia は trailing trivia として読む。
+/// 読めない文字が続くときは、次に読めるトークンの手前までを 1 つの [`TokenKind::Error`] トークンとする。
+/// range が空のときも幅 0 の [`TokenKind::Error`] トークンを返す
 pub fn lex(source: &Source, range: Range) -> LexResult {
     match skip_until_success(source, range) {
         Ok(lex_result) => lex_result,
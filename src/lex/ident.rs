@@ -1,3 +1,4 @@
+use super::keyword::KEYWORDS;
 use super::trivia::trivia_width;
 use super::LexResult;
 use crate::source::{Position, Range, Source};
@@ -27,10 +28,14 @@ pub fn lex_ident(source: &Source, range: Range) -> Option<LexResult> {
     }
 
     // 予約語は識別子ではない
-    if let &['l', 'e', 't'] = source.get(&Range {
+    let text = source.get(&Range {
         start: init_range_start,
         end: remaining_range.start,
-    }) {
+    });
+    if KEYWORDS
+        .iter()
+        .any(|(keyword, _)| text.iter().copied().eq(keyword.chars()))
+    {
         return None;
     }
 
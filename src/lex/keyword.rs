@@ -3,12 +3,21 @@ use super::LexResult;
 use crate::source::{starts_with, Range, Source};
 use crate::syntax_node::{SyntaxToken, TokenKind};
 
+// 予約語。識別子としては使えない
+pub const KEYWORDS: [(&str, TokenKind); 7] = [
+    ("let", TokenKind::Let),
+    ("if", TokenKind::If),
+    ("then", TokenKind::Then),
+    ("else", TokenKind::Else),
+    ("fun", TokenKind::Fun),
+    ("true", TokenKind::True),
+    ("false", TokenKind::False),
+];
+
 pub fn lex_keyword(source: &Source, range: Range) -> Option<LexResult> {
     let mut remaining_range = range;
-    use std::collections::HashMap;
-    let keywords: HashMap<&str, TokenKind> = vec![("let", TokenKind::Let)].into_iter().collect();
 
-    for (str, kind) in keywords {
+    for (str, kind) in KEYWORDS {
         if !starts_with(source, str, &remaining_range) {
             continue;
         }
@@ -38,4 +47,11 @@ fn test_keyword_token() {
 
     test("let", vec![Let]);
     test("let ", vec![Let]);
+    test("true", vec![True]);
+    test("false ", vec![False]);
+    test("trueish", vec![Ident]);
+    test("if then else", vec![If, Then, Else]);
+    test("iffy", vec![Ident]);
+    test("fun", vec![Fun]);
+    test("funny", vec![Ident]);
 }
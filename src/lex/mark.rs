@@ -3,43 +3,49 @@ use super::LexResult;
 use crate::source::{starts_with, Range, Source};
 use crate::syntax_node::{SyntaxToken, TokenKind};
 
+const MARKS: [(&str, TokenKind); 18] = [
+    ("+", TokenKind::Plus),
+    ("-", TokenKind::Minus),
+    ("*", TokenKind::Ast),
+    ("/", TokenKind::Slash),
+    ("=", TokenKind::Equal),
+    ("(", TokenKind::OpenParen),
+    (")", TokenKind::CloseParen),
+    (";", TokenKind::Semicolon),
+    ("==", TokenKind::EqualEqual),
+    ("!=", TokenKind::BangEqual),
+    ("<", TokenKind::Lt),
+    ("<=", TokenKind::LtEqual),
+    (">", TokenKind::Gt),
+    (">=", TokenKind::GtEqual),
+    ("&&", TokenKind::AmpAmp),
+    ("||", TokenKind::PipePipe),
+    ("!", TokenKind::Bang),
+    ("->", TokenKind::Arrow),
+];
+
 pub fn lex_mark(source: &Source, range: Range) -> Option<LexResult> {
     let mut remaining_range = range;
-    use std::collections::HashMap;
-    let operators: HashMap<&str, TokenKind> = vec![
-        ("+", TokenKind::Plus),
-        ("-", TokenKind::Minus),
-        ("*", TokenKind::Ast),
-        ("/", TokenKind::Slash),
-        ("=", TokenKind::Equal),
-        ("(", TokenKind::OpenParen),
-        (")", TokenKind::CloseParen),
-        (";", TokenKind::Semicolon),
-    ]
-    .into_iter()
-    .collect();
-
-    for (str, kind) in operators {
-        if !starts_with(source, str, &remaining_range) {
-            continue;
-        }
-        remaining_range.start.advance(str.len());
 
-        let trailing_trivia_width = trivia_width(source, remaining_range);
-        remaining_range.start.advance(trailing_trivia_width);
+    // 最も長く一致する記号を読む。e.g. "<=" は '<' と '=' ではなく 1 つのトークン
+    let (str, kind) = MARKS
+        .iter()
+        .filter(|(str, _)| starts_with(source, str, &remaining_range))
+        .max_by_key(|(str, _)| str.len())?;
+    remaining_range.start.advance(str.len());
 
-        return Some(LexResult {
-            token: SyntaxToken {
-                kind,
-                leading_trivia_width: 0,
-                token_width: str.len(),
-                trailing_trivia_width,
-            },
-            remaining_range,
-        });
-    }
+    let trailing_trivia_width = trivia_width(source, remaining_range);
+    remaining_range.start.advance(trailing_trivia_width);
 
-    None
+    Some(LexResult {
+        token: SyntaxToken {
+            kind: kind.clone(),
+            leading_trivia_width: 0,
+            token_width: str.len(),
+            trailing_trivia_width,
+        },
+        remaining_range,
+    })
 }
 
 #[test]
@@ -64,4 +70,19 @@ fn test_mark_token() {
     test("; ", vec![Semicolon]);
     test("( ", vec![OpenParen]);
     test(") ", vec![CloseParen]);
+
+    test(
+        "== != < <= > >= && || !",
+        vec![
+            EqualEqual, BangEqual, Lt, LtEqual, Gt, GtEqual, AmpAmp, PipePipe, Bang,
+        ],
+    );
+    // 最も長く一致する記号を読む
+    test("<==", vec![LtEqual, Equal]);
+    test("===", vec![EqualEqual, Equal]);
+    test("!!=", vec![Bang, BangEqual]);
+    test("->", vec![Arrow]);
+    test("- >", vec![Minus, Gt]);
+    test("&", vec![Error]);
+    test("& &", vec![Error, Error]);
 }
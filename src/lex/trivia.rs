@@ -1,6 +1,21 @@
 use crate::source::{starts_with, Position, Range, Source};
 
 pub fn trivia_width(source: &Source, range: Range) -> usize {
+    scan_trivia(source, range, |_| ())
+}
+
+// range の先頭にある trivia 中の "//" コメントの範囲を返す。範囲は "//" から改行の手前まで
+pub fn line_comments(source: &Source, range: Range) -> Vec<Range> {
+    let mut comments = vec![];
+    scan_trivia(source, range, |comment| comments.push(comment));
+    comments
+}
+
+// range の先頭にある trivia の幅を返す。"//" コメントを見つけるたびに on_line_comment を呼ぶ
+fn scan_trivia<F>(source: &Source, range: Range, mut on_line_comment: F) -> usize
+where
+    F: FnMut(Range),
+{
     let mut remaining_range = range;
     let start = remaining_range.start;
     while !remaining_range.is_empty() {
@@ -8,8 +23,13 @@ pub fn trivia_width(source: &Source, range: Range) -> usize {
         if c.is_whitespace() || c == '\t' {
             remaining_range.start.advance(1);
         } else if starts_with(source, "//", &remaining_range) {
+            let comment_start = remaining_range.start;
             remaining_range.start.advance(2);
             remaining_range.skip_until(|range: &Range| starts_with(source, "\n", range));
+            on_line_comment(Range {
+                start: comment_start,
+                end: remaining_range.start,
+            });
             if starts_with(source, "\n", &remaining_range) {
                 remaining_range.start.advance(1);
             }
@@ -39,3 +59,13 @@ fn test_trivia_width() {
     test(" // comment\n", 12);
     test(" /* comment */ a", 15);
 }
+
+#[test]
+fn test_line_comments() {
+    let source = Source::from_str(" // a\n/* // b */ //c");
+    let comments: Vec<String> = line_comments(&source, source.range())
+        .iter()
+        .map(|range| source.get(range).iter().collect())
+        .collect();
+    assert_eq!(comments, vec!["// a", "//c"]);
+}
@@ -0,0 +1,34 @@
+//! denvl 言語のフロントエンド
+//!
+//! ソースコードを読み込み ([`Source`])、字句解析 ([`lex()`]) と構文解析 ([`parse()`]) を行って
+//! ロスレスな構文木 ([`SyntaxNode`]) と診断情報 ([`Diagnostic`]) を得る。
+//! 構文解析はエラーがあっても最後まで進み、構文木は trivia (空白やコメント) を含めて元のソースコードを全て保持する。
+//!
+//! ```
+//! use denvl::{parse, Source};
+//!
+//! let source = Source::from_str("let a = 1;\na + 2 @");
+//! let (node, diagnostics) = parse(&source);
+//! // 構文木から元のソースコードを復元できる
+//! assert_eq!(node.text(&source), "let a = 1;\na + 2 @\n");
+//! assert_eq!(diagnostics.len(), 1);
+//! assert_eq!(diagnostics[0].code(), "E0003");
+//! ```
+//!
+//! - 型付きで構文木を辿るには [`ast`] を、評価するには [`eval`] を使う
+//! - 診断情報を表示するには [`diagnostic::render`]、[`diagnostic::json`]、[`diagnostic::sarif`] を使う
+
+pub mod annotation;
+pub mod ast;
+pub mod diagnostic;
+pub mod eval;
+pub mod lex;
+pub mod parse;
+pub mod source;
+pub mod syntax_node;
+
+pub use diagnostic::Diagnostic;
+pub use lex::lex;
+pub use parse::parse;
+pub use source::Source;
+pub use syntax_node::SyntaxNode;
@@ -1,17 +1,17 @@
-#![feature(assert_matches, box_patterns)]
+// denvl のコマンドラインインターフェース。言語の処理は denvl ライブラリ (lib.rs) にある
 
 mod commandline_client;
 mod consts;
-mod diagnostic;
-mod lex;
 mod named_pipe;
-mod parse;
 mod server;
-mod source;
-mod syntax_node;
-use clap::{command, Arg, Command};
+use clap::{command, value_parser, Arg, Command};
 
 const RUN_COMMAND: &str = "run";
+const CHECK_COMMAND: &str = "check";
+const FIX_COMMAND: &str = "fix";
+const PARSE_COMMAND: &str = "parse";
+const TEST_COMMAND: &str = "test";
+const EXPLAIN_COMMAND: &str = "explain";
 const SHUTDOWN_COMMAND: &str = "shutdown";
 const SERVER_COMMAND: &str = "__server";
 
@@ -19,23 +19,130 @@ fn main() {
     let matches = command!()
         .subcommand_required(true)
         .subcommand(
-            Command::new(RUN_COMMAND)
-                .about("compile and run specified denvl source file")
-                .arg(Arg::new("filename").required(true)),
+            with_compile_args(Command::new(RUN_COMMAND))
+                .about("compile and run specified denvl source file"),
+        )
+        .subcommand(
+            with_compile_args(Command::new(CHECK_COMMAND))
+                .about("check specified denvl source file without running it"),
+        )
+        .subcommand(
+            Command::new(FIX_COMMAND)
+                .about("apply machine-applicable suggestions to specified denvl source file")
+                .arg(Arg::new("filename").required(true))
+                .arg(color_arg()),
+        )
+        .subcommand(
+            Command::new(PARSE_COMMAND)
+                .about("print syntax tree of specified denvl source file")
+                .arg(Arg::new("filename").required(true))
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["sexp", "json"])
+                        .default_value("sexp")
+                        .help("output format of syntax tree"),
+                ),
+        )
+        .subcommand(
+            Command::new(TEST_COMMAND)
+                .about("check expectations written in comments of specified denvl source files")
+                .arg(
+                    Arg::new("filenames")
+                        .required(true)
+                        .num_args(1..)
+                        .value_name("FILENAME"),
+                ),
+        )
+        .subcommand(
+            Command::new(EXPLAIN_COMMAND)
+                .about("show detailed explanation of specified error code")
+                .arg(Arg::new("code").required(true)),
         )
         .subcommand(Command::new(SHUTDOWN_COMMAND).about("shutdown denvl server"))
         .subcommand(Command::new(SERVER_COMMAND).hide(true))
         .get_matches();
 
     match matches.subcommand() {
-        Some((RUN_COMMAND, sub_matches)) => {
+        Some((command @ (RUN_COMMAND | CHECK_COMMAND), sub_matches)) => {
+            let filename = sub_matches
+                .get_one::<String>("filename")
+                .expect("<filename> required");
+            let color = sub_matches
+                .get_one::<String>("color")
+                .expect("<color> has default value");
+            let message_format = sub_matches
+                .get_one::<String>("message-format")
+                .expect("<message-format> has default value");
+            let max_errors = sub_matches.get_one::<usize>("max-errors").copied();
+            if command == RUN_COMMAND {
+                commandline_client::run(filename, color, message_format, max_errors);
+            } else {
+                commandline_client::check(filename, color, message_format, max_errors);
+            }
+        }
+        Some((FIX_COMMAND, sub_matches)) => {
             let filename = sub_matches
                 .get_one::<String>("filename")
                 .expect("<filename> required");
-            commandline_client::run(filename);
+            let color = sub_matches
+                .get_one::<String>("color")
+                .expect("<color> has default value");
+            commandline_client::fix(filename, color);
+        }
+        Some((PARSE_COMMAND, sub_matches)) => {
+            let filename = sub_matches
+                .get_one::<String>("filename")
+                .expect("<filename> required");
+            let format = sub_matches
+                .get_one::<String>("format")
+                .expect("<format> has default value");
+            commandline_client::parse(filename, format);
+        }
+        Some((TEST_COMMAND, sub_matches)) => {
+            let filenames: Vec<&String> = sub_matches
+                .get_many::<String>("filenames")
+                .expect("<filenames> required")
+                .collect();
+            commandline_client::test(&filenames);
+        }
+        Some((EXPLAIN_COMMAND, sub_matches)) => {
+            let code = sub_matches
+                .get_one::<String>("code")
+                .expect("<code> required");
+            commandline_client::explain(code);
         }
         Some((SHUTDOWN_COMMAND, _)) => commandline_client::shutdown(),
         Some((SERVER_COMMAND, _)) => server::run(),
         _ => unreachable!(),
     }
 }
+
+fn with_compile_args(command: Command) -> Command {
+    command
+        .arg(Arg::new("filename").required(true))
+        .arg(color_arg())
+        .arg(
+            Arg::new("message-format")
+                .long("message-format")
+                .visible_alias("format")
+                .value_parser(["human", "json", "sarif"])
+                .default_value("human")
+                .help("output format of diagnostics"),
+        )
+        .arg(
+            Arg::new("max-errors")
+                .long("max-errors")
+                .value_name("N")
+                .value_parser(value_parser!(usize))
+                .help("maximum number of diagnostics to show"),
+        )
+}
+
+fn color_arg() -> Arg {
+    Arg::new("color")
+        .long("color")
+        .value_parser(["auto", "always", "never"])
+        .default_value("auto")
+        .help("coloring of diagnostics")
+}
@@ -9,6 +9,8 @@ pub struct NamedPipeServer {
     name: PathBuf,
 }
 
+// 中身はエラーを Debug で出力するときに使う
+#[allow(dead_code)]
 #[derive(Debug)]
 pub enum ServerCreationError {
     IO(std::io::Error),
@@ -62,11 +64,7 @@ impl NamedPipeServer {
 
     pub fn writeline(&mut self, line: String) -> Result<(), std::io::Error> {
         let pipename = make_server_to_client_pipename(&self.name);
-        let mut file = File::options()
-            .read(false)
-            .write(true)
-            .append(true)
-            .open(pipename)?;
+        let mut file = File::options().read(false).append(true).open(pipename)?;
         writeline(&mut file, line)?;
         Ok(())
     }
@@ -87,6 +85,8 @@ pub struct NamedPipeClient {
     name: PathBuf,
 }
 
+// 中身はエラーを Debug で出力するときに使う
+#[allow(dead_code)]
 #[derive(Debug)]
 pub enum ClientConnectionError {
     System(nix::Error),
@@ -116,11 +116,7 @@ impl NamedPipeClient {
 
     pub fn writeline(&mut self, line: String) -> Result<(), std::io::Error> {
         let pipename = make_client_to_server_pipename(&self.name);
-        let mut file = File::options()
-            .read(false)
-            .write(true)
-            .append(true)
-            .open(pipename)?;
+        let mut file = File::options().read(false).append(true).open(pipename)?;
         writeline(&mut file, line)?;
         Ok(())
     }
@@ -152,7 +148,9 @@ pub fn readline(file: &mut File) -> Result<String, std::io::Error> {
     loop {
         let mut buf: [char; 1] = [' '];
         unsafe {
-            file.read_exact(std::mem::transmute(buf.as_mut_slice()))?;
+            file.read_exact(std::mem::transmute::<&mut [char], &mut [u8]>(
+                buf.as_mut_slice(),
+            ))?;
         }
         if buf[0] == '\n' {
             break;
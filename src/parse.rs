@@ -1,54 +1,83 @@
 mod skip;
 mod test_util;
 
-mod additive_expr;
+mod binary_expr;
+mod call_expr;
+mod if_expr;
+mod lambda_expr;
 mod let_expr;
-mod multive_expr;
 mod primary_expr;
+#[cfg(test)]
+mod random_program;
+mod unary_expr;
 
 use crate::diagnostic::{
-    extra_token_error, missed_token_error, unexpected_token_error, Diagnostic,
+    extra_token_error, missed_token_error, missed_token_error_with_context,
+    missing_expression_error, Context, ContextKind, Diagnostic,
 };
 use crate::lex::{lex, trivia::trivia_width, LexResult};
-use crate::source::{Range, Source};
-use crate::syntax_node::{SyntaxNode, SyntaxToken, TokenKind};
+use crate::source::{Position, Range, Source};
+use crate::syntax_node::{
+    ChildNode, SyntaxNode, SyntaxToken, TokenKind, EXPR_BEGIN_TOKEN_KINDS,
+    PRIMARY_EXPR_BEGIN_TOKEN_KINDS,
+};
 use std::collections::VecDeque;
 
 /*
 
 expr =
   | "let" IDENT "=" expr ";" expr
-  | additive_expr
+  | "if" expr "then" expr "else" expr
+  | "fun" IDENT "->" expr
+  | binary_expr
+  ;
+
+// 下にある演算子ほど弱く結合する。どれも左結合
+binary_expr =
+  | binary_expr ("*"|"/") binary_expr
+  | binary_expr ("+"|"-") binary_expr
+  | binary_expr ("=="|"!="|"<"|"<="|">"|">=") binary_expr
+  | binary_expr "&&" binary_expr
+  | binary_expr "||" binary_expr
+  | unary_expr
   ;
 
-additive_expr =
-  | additive_expr ("+"|"-") additive_expr
-  | multive_expr
+unary_expr =
+  | ("-"|"+"|"!") unary_expr  // 二項演算子より強く結合する。e.g. -a * b は (-a) * b
+  | call_expr
   ;
 
-multive_expr =
-  | multive_expr ("*"|"/") multive_expr
+call_expr =
+  | call_expr "(" expr ")"  // 前置演算子より強く結合する。e.g. -f(1) は -(f(1))
   | primary_expr
   ;
 
 primary_expr =
   | IDENT
   | NUM
+  | "true"
+  | "false"
   | "(" expr ")"
   ;
 
 */
 
-pub struct ParseResult {
+pub(crate) struct ParseResult {
     pub node: SyntaxNode,
     pub diagnostics: VecDeque<Diagnostic>,
     pub remaining_range: Range,
 }
 
-pub struct Parser {
-    // is_let_init: bool, TODO: ';' の抜けを補完する。e.g. let a = 1 \n 2 は let a = 1; 2 と解釈されるようにする。
+pub(crate) struct Parser {
+    // let 式の初期化式を解析中かどうか
+    // 初期化式の途中で改行の後に式が始まったら ';' が抜けているとみなす。e.g. let a = 1 \n 2 は let a = 1; 2 と解釈する
+    is_let_init: bool,
 }
 
+/// ソースコード全体を構文解析し、構文木と診断情報を返す
+///
+/// エラーがあっても回復しながら最後まで解析し、panic しない。
+/// 構文木は読み飛ばしたトークンも trivia として保持するので、[`SyntaxNode::text`] で元のソースコードを復元できる
 pub fn parse(source: &Source) -> (SyntaxNode, VecDeque<Diagnostic>) {
     let mut parser = Parser::new();
     let mut range = source.range();
@@ -67,7 +96,7 @@ pub fn parse(source: &Source) -> (SyntaxNode, VecDeque<Diagnostic>) {
 
 impl Parser {
     pub fn new() -> Parser {
-        Parser { /* is_let_init: false */ }
+        Parser { is_let_init: false }
     }
 
     fn parse_toplevel(&mut self, source: &Source, range: Range) -> ParseResult {
@@ -82,6 +111,12 @@ impl Parser {
         node.extend_leading_trivia_width(skipped_width);
         diagnostics.append(&mut diagnostics_);
 
+        // 式の後に残ったトークンは余分なものとして読み飛ばす
+        let (skipped_width, mut diagnostics_, remaining_range) =
+            skip::until(source, remaining_range, vec![]);
+        node.extend_trailing_trivia_width(skipped_width);
+        diagnostics.append(&mut diagnostics_);
+
         ParseResult {
             node,
             diagnostics,
@@ -89,38 +124,54 @@ impl Parser {
         }
     }
 
+    // 演算子が書かれていない位置で、';' の抜けとして式を終えるべきかどうか
+    fn is_missing_semicolon(&self, source: &Source, pos: Position) -> bool {
+        self.is_let_init && follows_newline(source, pos)
+    }
+
     fn parse_expr(&mut self, source: &Source, range: Range) -> ParseResult {
+        let (skipped_width, mut diagnostics, range) = skip::until_not_error(source, range);
+
         let LexResult { token, .. } = lex(source, range);
-        use TokenKind::*;
-        match token.kind {
-            Let => self.parse_let_expr(source, range),
-            Ident | Number | OpenParen => self.parse_additive_expr(source, range),
+        let mut result = match token.kind {
+            TokenKind::Let => self.parse_let_expr(source, range),
+            TokenKind::If => self.parse_if_expr(source, range),
+            TokenKind::Fun => self.parse_lambda_expr(source, range),
+            // 前置できない二項演算子から始まる場合は、左辺が書かれていない二項演算として解析する
+            kind if EXPR_BEGIN_TOKEN_KINDS.contains(&kind)
+                || binary_expr::is_binary_operator(&kind) =>
+            {
+                self.parse_binary_expr(source, range)
+            }
             _ => {
-                // TODO: 'let' や識別子以外が来た時のエラー回復
+                // 式が書かれていない。トークンは消費せず、呼び出し元での回復に任せる
                 let mut diagnostics = VecDeque::new();
-                diagnostics.push_back(unexpected_token_error(
-                    range,
-                    vec![Let, Ident, Number, OpenParen],
-                    TokenKind::Error,
-                ));
+                diagnostics.push_back(missing_expression_error(range.start));
                 ParseResult {
-                    node: SyntaxNode::Error {
-                        token: SyntaxToken {
-                            kind: TokenKind::Error,
-                            leading_trivia_width: 0,
-                            token_width: range.width(),
-                            trailing_trivia_width: 0,
-                        },
-                    },
+                    node: SyntaxNode::missing(),
                     diagnostics,
-                    remaining_range: Range {
-                        start: range.end,
-                        end: range.end,
-                    },
+                    remaining_range: range,
                 }
             }
+        };
+        result.node.extend_leading_trivia_width(skipped_width);
+        diagnostics.append(&mut result.diagnostics);
+        result.diagnostics = diagnostics;
+        result
+    }
+}
+
+// pos の直前の空白に改行が含まれているかどうか
+fn follows_newline(source: &Source, mut pos: Position) -> bool {
+    while pos.0 > 0 {
+        pos.backward(1);
+        match source.at(pos) {
+            '\n' => return true,
+            c if c.is_whitespace() => continue,
+            _ => return false,
         }
     }
+    false
 }
 
 fn peek_token_kinds(source: &Source, mut range: Range, n: usize) -> Vec<TokenKind> {
@@ -138,12 +189,47 @@ fn peek_token_kinds(source: &Source, mut range: Range, n: usize) -> Vec<TokenKin
 }
 
 #[cfg(test)]
-use std::assert_matches::assert_matches;
+use test_util::assert_shape;
 
 #[test]
 fn only_trivia() {
     let source = Source::from_str(r" ");
     let (node, diagnostics) = parse(&source);
     assert_eq!(diagnostics.len(), 1);
-    assert_matches!(node, SyntaxNode::Error { .. });
+    assert_shape(&node, "Missing");
+    assert_eq!(node.text(&source), " \n");
+}
+
+#[test]
+fn missing_expression() {
+    use crate::diagnostic::DiagnosticError;
+
+    fn test(src: &str) -> SyntaxNode {
+        let source = Source::from_str(src);
+        let (node, diagnostics) = parse(&source);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            diagnostics[0],
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        ));
+        assert_eq!(node.text(&source), format!("{src}\n"));
+        node
+    }
+
+    assert_shape(&test("1 + "), "(BinOp _ _ Missing)");
+    assert_shape(&test("let a = ; b"), "(Let Missing Var)");
+    assert_shape(&test("let a = 1;"), "(Let _ Missing)");
+    assert_shape(&test("()"), "(Paren Missing)");
+    assert_shape(&test("let a = * 2; a"), "(Let (BinOp _ Missing _) _)");
+    assert_shape(&test("-"), "(Unary - Missing)");
+    assert_shape(&test("1 * -"), "(BinOp * Int (Unary - Missing))");
+}
+
+#[test]
+fn unary_at_toplevel() {
+    let source = Source::from_str("/* comment */ - 1 * 2");
+    let (node, diagnostics) = parse(&source);
+    assert!(diagnostics.is_empty());
+    assert_shape(&node, "(BinOp * (Unary - Int) Int)");
+    assert_eq!(node.text(&source), "/* comment */ - 1 * 2\n");
 }
@@ -0,0 +1,468 @@
+use super::*;
+
+/*
+binary_expr =
+  | binary_expr BINOP binary_expr
+  | unary_expr
+  ;
+
+BINOP の優先順位と結合性は BINARY_OPERATORS で決める
+*/
+
+#[derive(Clone, Copy)]
+enum Associativity {
+    Left,
+    // 累乗などの右結合の演算子を追加するときのため
+    #[allow(dead_code)]
+    Right,
+}
+
+struct BinaryOperator {
+    kind: TokenKind,
+    // 大きいほど強く結合する。0 は式の終わりを表すので使わない
+    binding_power: u8,
+    associativity: Associativity,
+    make_node: fn(SyntaxNode, SyntaxToken, SyntaxNode) -> SyntaxNode,
+}
+
+const BINARY_OPERATORS: [BinaryOperator; 12] = [
+    BinaryOperator {
+        kind: TokenKind::PipePipe,
+        binding_power: 1,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::AmpAmp,
+        binding_power: 2,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::EqualEqual,
+        binding_power: 3,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::BangEqual,
+        binding_power: 3,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::Lt,
+        binding_power: 3,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::LtEqual,
+        binding_power: 3,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::Gt,
+        binding_power: 3,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::GtEqual,
+        binding_power: 3,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::Plus,
+        binding_power: 4,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::Minus,
+        binding_power: 4,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::Ast,
+        binding_power: 5,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+    BinaryOperator {
+        kind: TokenKind::Slash,
+        binding_power: 5,
+        associativity: Associativity::Left,
+        make_node: make_binop_node,
+    },
+];
+
+// 演算子が書かれていないときは '+' だとみなす
+const IMPLICIT_OPERATOR_KIND: TokenKind = TokenKind::Plus;
+
+fn make_binop_node(
+    lhs_expr: SyntaxNode,
+    binop_token: SyntaxToken,
+    rhs_expr: SyntaxNode,
+) -> SyntaxNode {
+    SyntaxNode::BinOp {
+        lhs_expr: ChildNode::new(lhs_expr),
+        binop_token,
+        rhs_expr: ChildNode::new(rhs_expr),
+    }
+}
+
+fn find_binary_operator(kind: &TokenKind) -> Option<&'static BinaryOperator> {
+    BINARY_OPERATORS
+        .iter()
+        .find(|operator| &operator.kind == kind)
+}
+
+pub fn is_binary_operator(kind: &TokenKind) -> bool {
+    find_binary_operator(kind).is_some()
+}
+
+impl Parser {
+    pub fn parse_binary_expr(&mut self, source: &Source, range: Range) -> ParseResult {
+        self.parse_binary_expr_with_binding_power(source, range, 0)
+    }
+
+    // min_binding_power 以上の強さで結合する演算子だけを読み進める
+    fn parse_binary_expr_with_binding_power(
+        &mut self,
+        source: &Source,
+        range: Range,
+        min_binding_power: u8,
+    ) -> ParseResult {
+        let ParseResult {
+            node: mut expr,
+            mut diagnostics,
+            remaining_range: mut range,
+        } = self.parse_operand(source, range);
+
+        loop {
+            let mut expected: Vec<_> = BINARY_OPERATORS
+                .iter()
+                .map(|operator| operator.kind.clone())
+                .collect();
+            expected.append(&mut vec![
+                TokenKind::Semicolon,
+                TokenKind::CloseParen,
+                TokenKind::Then,
+                TokenKind::Else,
+            ]);
+            let (skipped_width, mut diagnostics_, range_) =
+                skip::until_expr_begin_or(source, range, expected);
+            let LexResult {
+                token,
+                remaining_range,
+            } = lex(source, range_);
+
+            let operator = if range_.is_empty() {
+                None
+            } else if let Some(operator) = find_binary_operator(&token.kind) {
+                Some((operator, token, remaining_range, false))
+            } else if PRIMARY_EXPR_BEGIN_TOKEN_KINDS.contains(&token.kind)
+                && !self.is_missing_semicolon(source, range_.start)
+            {
+                // 式の始まり
+                // 演算子書き忘れ
+                let operator = find_binary_operator(&IMPLICIT_OPERATOR_KIND).unwrap();
+                Some((
+                    operator,
+                    SyntaxToken::make_empty(operator.kind.clone()),
+                    range_,
+                    true,
+                ))
+            } else {
+                None
+            };
+
+            let (operator, mut binop_token, remaining_range, is_implicit) = match operator {
+                Some(operator) if operator.0.binding_power >= min_binding_power => operator,
+                Some(_) => {
+                    // より弱く結合する演算子は呼び出し元で読む。読み飛ばしたトークンもそこで読み直す
+                    return ParseResult {
+                        node: expr,
+                        diagnostics,
+                        remaining_range: range,
+                    };
+                }
+                None => {
+                    // 式の終わり。読み飛ばしたトークンは一番外側の式の trivia とする
+                    if min_binding_power == 0 {
+                        diagnostics.append(&mut diagnostics_);
+                        expr.extend_trailing_trivia_width(skipped_width);
+                        range = range_;
+                    }
+                    return ParseResult {
+                        node: expr,
+                        diagnostics,
+                        remaining_range: range,
+                    };
+                }
+            };
+            diagnostics.append(&mut diagnostics_);
+            range = remaining_range;
+            binop_token.leading_trivia_width += skipped_width;
+
+            if is_implicit {
+                diagnostics.push_back(missed_token_error(
+                    range.start,
+                    BINARY_OPERATORS
+                        .iter()
+                        .map(|operator| operator.kind.clone())
+                        .collect(),
+                ));
+            } else {
+                let (skipped_width, mut diagnostics_, range_) =
+                    skip::until_not_error(source, range);
+                diagnostics.append(&mut diagnostics_);
+                binop_token.trailing_trivia_width += skipped_width;
+                range = range_;
+            }
+
+            let rhs_min_binding_power = match operator.associativity {
+                Associativity::Left => operator.binding_power + 1,
+                Associativity::Right => operator.binding_power,
+            };
+            let mut rhs_expr_result =
+                self.parse_binary_expr_with_binding_power(source, range, rhs_min_binding_power);
+            diagnostics.append(&mut rhs_expr_result.diagnostics);
+            range = rhs_expr_result.remaining_range;
+
+            expr = (operator.make_node)(expr, binop_token, rhs_expr_result.node);
+        }
+    }
+
+    // 二項演算子のオペランド。書かれていなければ Missing を返す
+    fn parse_operand(&mut self, source: &Source, range: Range) -> ParseResult {
+        self.parse_unary_expr(source, range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::test_util::*;
+    use super::{ParseResult, Parser, Range, Source, BINARY_OPERATORS};
+    use crate::diagnostic::{Diagnostic, DiagnosticError};
+    use crate::syntax_node::BINARY_OPERATOR_TOKEN_KINDS;
+
+    fn parse(source: &Source, range: Range) -> ParseResult {
+        let mut parser = Parser::new();
+        parser.parse_binary_expr(source, range)
+    }
+
+    // 診断メッセージで "an operator" とまとめる集合と同じ演算子を扱う
+    #[test]
+    fn test_binary_operator_token_kinds() {
+        for kind in BINARY_OPERATOR_TOKEN_KINDS {
+            assert!(
+                BINARY_OPERATORS
+                    .iter()
+                    .any(|operator| operator.kind == kind),
+                "{kind:?}"
+            );
+        }
+        assert_eq!(BINARY_OPERATORS.len(), BINARY_OPERATOR_TOKEN_KINDS.len());
+    }
+
+    #[test]
+    fn test_normal_scenarios() {
+        check_tree_pattern!(parse, "a + 1", "(BinOp + Var Int)");
+        check_tree_pattern!(parse, "a - 1", "(BinOp - Var Int)");
+
+        check_tree_pattern!(parse, "a + 1 - 2", "(BinOp - (BinOp + Var Int) Int)");
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        check_tree_pattern!(parse, "1 + 2 * 3", "(BinOp + Int (BinOp * Int Int))");
+        // 前置演算子は二項演算子より強く結合する
+        check_tree_pattern!(parse, "-a * b", "(BinOp * (Unary - Var) Var)");
+        check_tree_pattern!(parse, "1 + + 2", "(BinOp + Int (Unary + Int))");
+        check_tree_pattern!(parse, "1 * - 2", "(BinOp * Int (Unary - Int))");
+        check_tree_pattern!(parse, "1 - -2", "(BinOp - Int (Unary - Int))");
+
+        check_tree_pattern!(
+            parse,
+            "a + 1 < b * 2",
+            "(BinOp < (BinOp + Var Int) (BinOp * Var Int))"
+        );
+        check_tree_pattern!(
+            parse,
+            "a == b && c != d || e",
+            "(BinOp || (BinOp && (BinOp == Var Var) (BinOp != Var Var)) Var)"
+        );
+        check_tree_pattern!(parse, "a || b && c", "(BinOp || Var (BinOp && Var Var))");
+        check_tree_pattern!(parse, "!a == b", "(BinOp == (Unary ! Var) Var)");
+        check_tree_pattern!(parse, "a <= b >= c", "(BinOp >= (BinOp <= Var Var) Var)");
+    }
+
+    #[test]
+    fn test_normal_scenarios_with_trivia() {
+        check_tree_pattern!(parse, "a + 1 ", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a // comment\n + 1 ", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a + // comment\n 1 ", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a + 1 // comment", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a /* comment */ + 1 ", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a + /* comment*/ 1 ", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a + 1 /* comment */", "(BinOp _ Var Int)");
+    }
+
+    #[test]
+    fn test_normal_scenarios_with_errors() {
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "a @ + 1 ",
+            "(BinOp _ Var Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "a + @ 1 ",
+            "(BinOp _ Var Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "a + 1 @",
+            "(BinOp _ Var Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "1 2", // 演算子が抜けている
+            "(BinOp _ Int Int)",
+            Diagnostic::Error(DiagnosticError::MissedToken { .. })
+        );
+
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "1 @ 2", // 演算子が抜けており、解釈できないトークンがある
+            "(BinOp _ Int Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+            Diagnostic::Error(DiagnosticError::MissedToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "1 + @ 2",
+            "(BinOp _ Int Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "1 @ + 2",
+            "(BinOp _ Int Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "1 @ = + 2", // 余分な '=' トークンがある
+            "(BinOp _ Int Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+            Diagnostic::Error(DiagnosticError::ExtraToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "1 + * 2", // 式が抜けている
+            "(BinOp + Int (BinOp * Missing Int))",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "(1+)", // 式が抜けている
+            "(Paren (BinOp _ Int Missing))",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "(let a = 1 + ; a)", // 式が抜けている
+            "(Paren (Let (BinOp _ Int Missing) _))",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+    }
+
+    #[test]
+    fn test_multiplicative_normal_scenarios() {
+        check_tree_pattern!(parse, "a * 1", "(BinOp * Var Int)");
+        check_tree_pattern!(parse, "a / 1", "(BinOp / Var Int)");
+
+        check_tree_pattern!(parse, "a * 1 / 2", "(BinOp / (BinOp * Var Int) Int)");
+    }
+
+    #[test]
+    fn test_multiplicative_normal_scenarios_with_trivia() {
+        check_tree_pattern!(parse, "a * 1 ", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a // comment\n * 1 ", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a * // comment\n 1 ", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a * 1 // comment", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a /* comment */ * 1 ", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a * /* comment*/ 1 ", "(BinOp _ Var Int)");
+        check_tree_pattern!(parse, "a * 1 /* comment */", "(BinOp _ Var Int)");
+    }
+
+    #[test]
+    fn test_multiplicative_normal_scenarios_with_errors() {
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "a @ * 1 ",
+            "(BinOp _ Var Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "a * @ 1 ",
+            "(BinOp _ Var Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "a * 1 @",
+            "(BinOp _ Var Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "1 * @ 2",
+            "(BinOp _ Int Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "1 @ * 2",
+            "(BinOp _ Int Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "1 @ = * 2", // 余分な '=' トークンがある
+            "(BinOp _ Int Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+            Diagnostic::Error(DiagnosticError::ExtraToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "1 * * 2", // 式が抜けている
+            "(BinOp _ (BinOp _ Int Missing) Int)",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "(1*)", // 式が抜けている
+            "(Paren (BinOp _ Int Missing))",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "(let a = 1 * ; a)", // 式が抜けている
+            "(Paren (Let (BinOp _ Int Missing) _))",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use super::*;
+
+/*
+call_expr =
+  | call_expr "(" expr ")"
+  | primary_expr
+  ;
+*/
+
+impl Parser {
+    // 書かれていなければ Missing を返し、トークンは消費しない
+    pub fn parse_call_expr(&mut self, source: &Source, range: Range) -> ParseResult {
+        let ParseResult {
+            node: mut expr,
+            mut diagnostics,
+            remaining_range: mut range,
+        } = self.parse_primary_expr(source, range);
+
+        // 実引数の括弧は括弧式と同じ形なので、括弧式として読んでから組み替える
+        // let 式の初期化式では、改行の後の '(' は ';' が抜けた後の式の始まりとみなす
+        // ただし括弧の後に ';' が続くなら、初期化式の続きとして関数適用と読む。e.g. let g = f \n (1); g
+        while lex(source, range).token.kind == TokenKind::OpenParen
+            && (!self.is_missing_semicolon(source, range.start)
+                || args_end_with_semicolon(source, range))
+        {
+            let mut arg_result = self.parse_primary_expr(source, range);
+            diagnostics.append(&mut arg_result.diagnostics);
+            range = arg_result.remaining_range;
+            let SyntaxNode::Paren {
+                open_paren_token,
+                inner_expr,
+                close_paren_token,
+            } = arg_result.node
+            else {
+                unreachable!("'(' begins a parenthesized expression");
+            };
+            expr = SyntaxNode::Call {
+                callee_expr: ChildNode::new(expr),
+                open_paren_token,
+                arg_expr: inner_expr,
+                close_paren_token,
+            };
+        }
+
+        ParseResult {
+            node: expr,
+            diagnostics,
+            remaining_range: range,
+        }
+    }
+}
+
+// range が '(' から始まるとき、対応する ')' まで (続けて '(' があればその括弧も) を読み飛ばした直後が ';' かどうか
+fn args_end_with_semicolon(source: &Source, mut range: Range) -> bool {
+    let mut depth = 0;
+    while !range.is_empty() {
+        let LexResult {
+            token,
+            remaining_range,
+        } = lex(source, range);
+        range = remaining_range;
+        match token.kind {
+            TokenKind::OpenParen => depth += 1,
+            TokenKind::CloseParen => {
+                depth -= 1;
+                if depth == 0 {
+                    match lex(source, range).token.kind {
+                        TokenKind::OpenParen => {}
+                        kind => return kind == TokenKind::Semicolon,
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::test_util::*;
+    use super::{ParseResult, Parser, Range, Source};
+    use crate::diagnostic::{Diagnostic, DiagnosticError};
+
+    fn parse(source: &Source, range: Range) -> ParseResult {
+        let mut parser = Parser::new();
+        parser.parse_call_expr(source, range)
+    }
+
+    #[test]
+    fn test_normal_scenarios() {
+        check_tree_pattern!(parse, "f(1)", "(Call Var Int)");
+        check_tree_pattern!(parse, "f(1)(a)", "(Call (Call Var Int) Var)");
+        check_tree_pattern!(parse, "(f)(1 + 2)", "(Call (Paren Var) (BinOp + Int Int))");
+        check_tree_pattern!(parse, "f", "Var");
+    }
+
+    #[test]
+    fn test_normal_scenarios_with_trivia() {
+        check_tree_pattern!(parse, "f (1) ", "(Call Var Int)");
+        check_tree_pattern!(parse, "f // comment\n (1)", "(Call Var Int)");
+        check_tree_pattern!(parse, "f( /* comment */ 1 )", "(Call Var Int)");
+    }
+
+    #[test]
+    fn test_normal_scenarios_with_errors() {
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "f(1",
+            "(Call Var Int)",
+            Diagnostic::Error(DiagnosticError::MissedToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "f()",
+            "(Call Var Missing)",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "f(@ 1)",
+            "(Call Var Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+    }
+}
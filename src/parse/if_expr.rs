@@ -0,0 +1,208 @@
+use super::*;
+
+// "if" expr "then" expr "else" expr
+
+impl Parser {
+    pub fn parse_if_expr(&mut self, source: &Source, range: Range) -> ParseResult {
+        let LexResult {
+            token,
+            remaining_range,
+        } = lex(source, range);
+        assert_eq!(token.kind, TokenKind::If);
+        let if_token_range = Range {
+            start: range.start,
+            end: range.start + token.token_width,
+        };
+        let if_token = token;
+
+        let mut diagnostics = VecDeque::new();
+
+        // 条件式と then 節は予約語で区切られるので、改行による ';' の抜けの判定はしない
+        let is_let_init = std::mem::replace(&mut self.is_let_init, false);
+        let (cond_expr, then_token, range) = self.parse_if_part(
+            source,
+            remaining_range,
+            TokenKind::Then,
+            Context {
+                kind: ContextKind::IfThen,
+                range: if_token_range,
+            },
+            &mut diagnostics,
+        );
+        let (then_expr, else_token, range) = self.parse_if_part(
+            source,
+            range,
+            TokenKind::Else,
+            Context {
+                kind: ContextKind::IfElse,
+                range: if_token_range,
+            },
+            &mut diagnostics,
+        );
+        self.is_let_init = is_let_init;
+
+        let mut else_result = self.parse_expr(source, range);
+        diagnostics.append(&mut else_result.diagnostics);
+
+        ParseResult {
+            node: SyntaxNode::If {
+                if_token,
+                cond_expr: ChildNode::new(cond_expr),
+                then_token,
+                then_expr: ChildNode::new(then_expr),
+                else_token,
+                else_expr: ChildNode::new(else_result.node),
+            },
+            diagnostics,
+            remaining_range: else_result.remaining_range,
+        }
+    }
+
+    // 式とそれに続く予約語 (then または else) を読む
+    // 予約語が抜けていたら幅 0 のトークンで補い、読み飛ばしたトークンは式の trailing trivia とする
+    fn parse_if_part(
+        &mut self,
+        source: &Source,
+        range: Range,
+        keyword_kind: TokenKind,
+        context: Context,
+        diagnostics: &mut VecDeque<Diagnostic>,
+    ) -> (SyntaxNode, SyntaxToken, Range) {
+        let mut expr_result = self.parse_expr(source, range);
+        let mut expr = expr_result.node;
+        let range = expr_result.remaining_range;
+        // 予約語が抜けていたら式の直後 (trivia の前) に補う
+        let mut expr_end = range.start;
+        expr_end.backward(expr.trailing_trivia_width());
+        diagnostics.append(&mut expr_result.diagnostics);
+
+        let (skipped_width, mut diagnostics_, mut range) =
+            skip::until_expr_begin_or(source, range, vec![TokenKind::Then, TokenKind::Else]);
+        diagnostics.append(&mut diagnostics_);
+
+        let keyword_lex_result = lex(source, range);
+        let keyword_token = if keyword_lex_result.token.kind == keyword_kind {
+            range = keyword_lex_result.remaining_range;
+            let mut token = keyword_lex_result.token;
+            token.leading_trivia_width += skipped_width;
+            token
+        } else {
+            diagnostics.push_back(missed_token_error_with_context(
+                expr_end,
+                vec![keyword_kind.clone()],
+                context,
+            ));
+            expr.extend_trailing_trivia_width(skipped_width);
+            SyntaxToken::make_empty(keyword_kind)
+        };
+
+        (expr, keyword_token, range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::test_util::*;
+    use super::{ParseResult, Parser, Position, Range, Source};
+    use crate::diagnostic::{Diagnostic, DiagnosticError};
+    use crate::syntax_node::TokenKind;
+
+    fn parse(source: &Source, range: Range) -> ParseResult {
+        let mut parser = Parser::new();
+        parser.parse_if_expr(source, range)
+    }
+
+    #[test]
+    fn test_normal_scenarios() {
+        check_tree_pattern!(parse, "if a then 1 else 2", "(If Var Int Int)");
+        check_tree_pattern!(
+            parse,
+            "if a < b then a else b + 1",
+            "(If (BinOp < Var Var) Var (BinOp + Var Int))"
+        );
+        // else 節はできるだけ長く読む
+        check_tree_pattern!(
+            parse,
+            "if a then 1 else if b then 2 else 3",
+            "(If Var Int (If Var Int Int))"
+        );
+        check_tree_pattern!(
+            parse,
+            "if a then let b = 1; b else 2",
+            "(If Var (Let Int Var) Int)"
+        );
+    }
+
+    #[test]
+    fn test_normal_scenarios_with_trivia() {
+        check_tree_pattern!(parse, "if a then 1 else 2 ", "(If Var Int Int)");
+        check_tree_pattern!(parse, "if // comment\n a then 1 else 2", "(If Var Int Int)");
+        check_tree_pattern!(parse, "if a // comment\n then 1 else 2", "(If Var Int Int)");
+        check_tree_pattern!(parse, "if a then 1 // comment\n else 2", "(If Var Int Int)");
+        check_tree_pattern!(
+            parse,
+            "if a then 1 else /* comment */ 2",
+            "(If Var Int Int)"
+        );
+        check_tree_pattern!(
+            parse,
+            "if a then 1 else 2 /* comment */",
+            "(If Var Int Int)"
+        );
+    }
+
+    #[test]
+    fn test_normal_scenarios_with_errors() {
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "if a let b = 1; b else 2",
+            "(If Var (Let Int Var) Int)",
+            Diagnostic::Error(DiagnosticError::MissedToken {
+                pos: Position(4),
+                ..
+            })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "if a then 1 2",
+            "(If Var (BinOp + Int Int) Missing)",
+            Diagnostic::Error(DiagnosticError::MissedToken { .. })
+            Diagnostic::Error(DiagnosticError::MissedToken { .. })
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "if a then 1 ; else 2",
+            "(If Var Int Int)",
+            Diagnostic::Error(DiagnosticError::ExtraToken {
+                kind: TokenKind::Semicolon,
+                ..
+            })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "if a ; 1 else 2",
+            "(If Var Int Int)",
+            Diagnostic::Error(DiagnosticError::ExtraToken {
+                kind: TokenKind::Semicolon,
+                ..
+            })
+            Diagnostic::Error(DiagnosticError::MissedToken {
+                pos: Position(4),
+                ..
+            })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "if then 1 else 2",
+            "(If Missing Int Int)",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "if a then 1 else",
+            "(If Var Int Missing)",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use super::*;
+
+// "fun" IDENT "->" expr
+
+impl Parser {
+    pub fn parse_lambda_expr(&mut self, source: &Source, mut range: Range) -> ParseResult {
+        let LexResult {
+            token,
+            remaining_range,
+        } = lex(source, range);
+        assert_eq!(token.kind, TokenKind::Fun);
+        let fun_token_range = Range {
+            start: range.start,
+            end: range.start + token.token_width,
+        };
+        let fun_token = token;
+        range = remaining_range;
+
+        let mut diagnostics = VecDeque::new();
+
+        // 前置演算子は仮引数の位置に紛れ込んだものとして読み飛ばす。e.g. fun - x -> x
+        let mut expected: Vec<_> = PRIMARY_EXPR_BEGIN_TOKEN_KINDS.into_iter().collect();
+        expected.append(&mut vec![
+            TokenKind::Let,
+            TokenKind::If,
+            TokenKind::Fun,
+            TokenKind::Arrow,
+        ]);
+        let (skipped_width, mut diagnostics_, mut range) = skip::until(source, range, expected);
+        diagnostics.append(&mut diagnostics_);
+
+        let tokens = peek_token_kinds(source, range, 2);
+        let (mut param_token, arrow_token) = match tokens.as_slice() {
+            [TokenKind::Ident, TokenKind::Arrow] => {
+                let param_lex_result = lex(source, range);
+                assert_eq!(param_lex_result.token.kind, TokenKind::Ident);
+                range = param_lex_result.remaining_range;
+                let arrow_lex_result = lex(source, range);
+                assert_eq!(arrow_lex_result.token.kind, TokenKind::Arrow);
+                range = arrow_lex_result.remaining_range;
+                (param_lex_result.token, arrow_lex_result.token)
+            }
+            [TokenKind::Ident, _] => {
+                let param_lex_result = lex(source, range);
+                assert_eq!(param_lex_result.token.kind, TokenKind::Ident);
+                range = param_lex_result.remaining_range;
+                diagnostics.push_back(missed_token_error_with_context(
+                    range.start,
+                    vec![TokenKind::Arrow],
+                    Context {
+                        kind: ContextKind::FunArrow,
+                        range: fun_token_range,
+                    },
+                ));
+                (
+                    param_lex_result.token,
+                    SyntaxToken::make_empty(TokenKind::Arrow),
+                )
+            }
+            [TokenKind::Arrow, _] => {
+                diagnostics.push_back(missed_token_error_with_context(
+                    range.start,
+                    vec![TokenKind::Ident],
+                    Context {
+                        kind: ContextKind::FunParam,
+                        range: fun_token_range,
+                    },
+                ));
+                let arrow_lex_result = lex(source, range);
+                assert_eq!(arrow_lex_result.token.kind, TokenKind::Arrow);
+                range = arrow_lex_result.remaining_range;
+                (
+                    SyntaxToken::make_empty(TokenKind::Ident),
+                    arrow_lex_result.token,
+                )
+            }
+            _ => {
+                // 関数として読めないので 'fun' を余分なトークンとして読み飛ばし、残りを式として解析する
+                let mut result = self.parse_expr(source, range);
+                result
+                    .node
+                    .extend_leading_trivia_width(fun_token.full_width() + skipped_width);
+                diagnostics.push_front(extra_token_error(fun_token_range, TokenKind::Fun));
+                diagnostics.append(&mut result.diagnostics);
+                result.diagnostics = diagnostics;
+                return result;
+            }
+        };
+        param_token.leading_trivia_width += skipped_width;
+
+        // 本体はできるだけ長く読む。e.g. fun x -> x + 1 の本体は x + 1
+        let mut body_result = self.parse_expr(source, range);
+        diagnostics.append(&mut body_result.diagnostics);
+
+        ParseResult {
+            node: SyntaxNode::Lambda {
+                fun_token,
+                param_token,
+                arrow_token,
+                body_expr: ChildNode::new(body_result.node),
+            },
+            diagnostics,
+            remaining_range: body_result.remaining_range,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::test_util::*;
+    use super::{ParseResult, Parser, Range, Source};
+    use crate::diagnostic::{Diagnostic, DiagnosticError};
+    use crate::syntax_node::TokenKind;
+
+    fn parse(source: &Source, range: Range) -> ParseResult {
+        let mut parser = Parser::new();
+        parser.parse_lambda_expr(source, range)
+    }
+
+    #[test]
+    fn test_normal_scenarios() {
+        check_tree_pattern!(parse, "fun x -> x", "(Lambda Var)");
+        check_tree_pattern!(parse, "fun x -> x + 1", "(Lambda (BinOp + Var Int))");
+        check_tree_pattern!(parse, "fun x -> fun y -> x", "(Lambda (Lambda Var))");
+        check_tree_pattern!(parse, "fun x->x", "(Lambda Var)");
+    }
+
+    #[test]
+    fn test_normal_scenarios_with_trivia() {
+        check_tree_pattern!(parse, "fun x -> x ", "(Lambda Var)");
+        check_tree_pattern!(parse, "fun // comment\n x -> x", "(Lambda Var)");
+        check_tree_pattern!(parse, "fun x /* comment */ -> x", "(Lambda Var)");
+        check_tree_pattern!(parse, "fun x -> // comment\n x", "(Lambda Var)");
+        check_tree_pattern!(parse, "fun x -> x // comment", "(Lambda Var)");
+    }
+
+    #[test]
+    fn test_normal_scenarios_with_errors() {
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "fun x x",
+            "(Lambda Var)",
+            Diagnostic::Error(DiagnosticError::MissedToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "fun -> 1",
+            "(Lambda Int)",
+            Diagnostic::Error(DiagnosticError::MissedToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "fun @ x -> x",
+            "(Lambda Var)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "fun 1",
+            "Int",
+            Diagnostic::Error(DiagnosticError::ExtraToken {
+                kind: TokenKind::Fun,
+                ..
+            })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "fun x ->",
+            "(Lambda Missing)",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+    }
+}
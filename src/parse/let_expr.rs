@@ -9,13 +9,24 @@ impl Parser {
             remaining_range,
         } = lex(source, range);
         assert_eq!(token.kind, TokenKind::Let);
+        let let_token_range = Range {
+            start: range.start,
+            end: range.start + token.token_width,
+        };
         let let_token = token;
         range = remaining_range;
 
         let mut diagnostics = VecDeque::new();
 
-        let (skipped_width, mut diagnostics_, mut range) =
-            skip::until_expr_begin_or(source, range, vec![TokenKind::Equal]);
+        // 前置演算子は変数名の位置に紛れ込んだものとして読み飛ばす。e.g. let - = 1; a
+        let mut expected: Vec<_> = PRIMARY_EXPR_BEGIN_TOKEN_KINDS.into_iter().collect();
+        expected.append(&mut vec![
+            TokenKind::Let,
+            TokenKind::If,
+            TokenKind::Fun,
+            TokenKind::Equal,
+        ]);
+        let (skipped_width, mut diagnostics_, mut range) = skip::until(source, range, expected);
         diagnostics.append(&mut diagnostics_);
 
         let tokens = peek_token_kinds(source, range, 2);
@@ -33,14 +44,28 @@ impl Parser {
                 let ident_lex_result = lex(source, range);
                 assert_eq!(ident_lex_result.token.kind, TokenKind::Ident);
                 range = ident_lex_result.remaining_range;
-                diagnostics.push_back(missed_token_error(range.start, vec![TokenKind::Equal]));
+                diagnostics.push_back(missed_token_error_with_context(
+                    range.start,
+                    vec![TokenKind::Equal],
+                    Context {
+                        kind: ContextKind::LetEqual,
+                        range: let_token_range,
+                    },
+                ));
                 (
                     ident_lex_result.token,
                     SyntaxToken::make_empty(TokenKind::Equal),
                 )
             }
             [TokenKind::Equal, _] => {
-                diagnostics.push_back(missed_token_error(range.start, vec![TokenKind::Ident]));
+                diagnostics.push_back(missed_token_error_with_context(
+                    range.start,
+                    vec![TokenKind::Ident],
+                    Context {
+                        kind: ContextKind::LetIdent,
+                        range: let_token_range,
+                    },
+                ));
                 let equal_lex_result = lex(source, range);
                 assert_eq!(equal_lex_result.token.kind, TokenKind::Equal);
                 range = equal_lex_result.remaining_range;
@@ -50,25 +75,27 @@ impl Parser {
                 )
             }
             _ => {
+                // let 式として読めないので 'let' を余分なトークンとして読み飛ばし、残りを式として解析する
                 let mut result = self.parse_expr(source, range);
-                let LexResult {
-                    remaining_range, ..
-                } = lex(source, range);
-                let let_token_range = Range {
-                    start: range.start,
-                    end: remaining_range.start,
-                };
                 result
-                    .diagnostics
-                    .push_back(extra_token_error(let_token_range, TokenKind::Let));
+                    .node
+                    .extend_leading_trivia_width(let_token.full_width() + skipped_width);
+                diagnostics.push_front(extra_token_error(let_token_range, TokenKind::Let));
+                diagnostics.append(&mut result.diagnostics);
+                result.diagnostics = diagnostics;
                 return result;
             }
         };
         ident_token.leading_trivia_width += skipped_width;
 
+        let is_let_init = std::mem::replace(&mut self.is_let_init, true);
         let mut init_result = self.parse_expr(source, range);
+        self.is_let_init = is_let_init;
         let init_expr = init_result.node;
         range = init_result.remaining_range;
+        // ';' が抜けていたら初期化式の直後 (trivia の前) に補う
+        let mut init_end = range.start;
+        init_end.backward(init_expr.trailing_trivia_width());
         diagnostics.append(&mut init_result.diagnostics);
 
         let (skipped_width, mut diagnostics_, mut range) =
@@ -84,7 +111,14 @@ impl Parser {
                 token.leading_trivia_width += skipped_width;
                 token
             } else {
-                diagnostics.push_back(missed_token_error(range.start, vec![TokenKind::Semicolon]));
+                diagnostics.push_back(missed_token_error_with_context(
+                    init_end,
+                    vec![TokenKind::Semicolon],
+                    Context {
+                        kind: ContextKind::LetSemicolon,
+                        range: let_token_range,
+                    },
+                ));
                 SyntaxToken::make_empty(TokenKind::Semicolon)
             }
         };
@@ -104,9 +138,9 @@ impl Parser {
                 let_token,
                 ident_token,
                 equal_token,
-                init_expr: Box::new(init_expr),
+                init_expr: ChildNode::new(init_expr),
                 semicolon_token,
-                body_expr: Box::new(body_expr),
+                body_expr: ChildNode::new(body_expr),
             },
             diagnostics,
             remaining_range: range,
@@ -117,9 +151,9 @@ impl Parser {
 #[cfg(test)]
 mod test {
     use super::test_util::*;
-    use super::{ParseResult, Parser, Range, Source};
+    use super::{ParseResult, Parser, Position, Range, Source};
     use crate::diagnostic::{Diagnostic, DiagnosticError};
-    use crate::syntax_node::{SyntaxNode, TokenKind};
+    use crate::syntax_node::TokenKind;
 
     fn parse(source: &Source, range: Range) -> ParseResult {
         let mut parser = Parser::new();
@@ -128,137 +162,32 @@ mod test {
 
     #[test]
     fn test_normal_scenarios() {
+        check_tree_pattern!(parse, "let a = 1; a", "(Let Int Var)");
+        // 改行の後の '(' でも、括弧の後に ';' が続けば初期化式の続きとして関数適用と読む
+        check_tree_pattern!(parse, "let g = f\n(1); g", "(Let (Call Var Int) Var)");
         check_tree_pattern!(
             parse,
-            "let a = 1; a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
+            "let g = f\n(1)\n(2); g",
+            "(Let (Call (Call Var Int) Int) Var)"
         );
     }
 
     #[test]
     fn test_normal_scenarios_with_trivia() {
-        check_tree_pattern!(
-            parse,
-            "let a = 1; a ",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let // comment \n a = 1; a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let a // comment \n = 1; a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let a = // comment \n 1; a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let a = 1 // comment \n ; a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let a = 1; // comment \n a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let a = 1; a // comment",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
+        check_tree_pattern!(parse, "let a = 1; a ", "(Let Int Var)");
+        check_tree_pattern!(parse, "let // comment \n a = 1; a", "(Let Int Var)");
+        check_tree_pattern!(parse, "let a // comment \n = 1; a", "(Let Int Var)");
+        check_tree_pattern!(parse, "let a = // comment \n 1; a", "(Let Int Var)");
+        check_tree_pattern!(parse, "let a = 1 // comment \n ; a", "(Let Int Var)");
+        check_tree_pattern!(parse, "let a = 1; // comment \n a", "(Let Int Var)");
+        check_tree_pattern!(parse, "let a = 1; a // comment", "(Let Int Var)");
 
-        check_tree_pattern!(
-            parse,
-            "let /* comment */ a = 1; a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let a /* comment */ = 1; a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let a = /* comment */ 1; a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let a = 1 /* comment */ ; a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let a = 1; /* comment */ a",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "let a = 1; a /* comment */",
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
+        check_tree_pattern!(parse, "let /* comment */ a = 1; a", "(Let Int Var)");
+        check_tree_pattern!(parse, "let a /* comment */ = 1; a", "(Let Int Var)");
+        check_tree_pattern!(parse, "let a = /* comment */ 1; a", "(Let Int Var)");
+        check_tree_pattern!(parse, "let a = 1 /* comment */ ; a", "(Let Int Var)");
+        check_tree_pattern!(parse, "let a = 1; /* comment */ a", "(Let Int Var)");
+        check_tree_pattern!(parse, "let a = 1; a /* comment */", "(Let Int Var)");
     }
 
     #[test]
@@ -266,7 +195,7 @@ mod test {
         check_tree_and_diagnostic_pattern!(
             parse,
             "let 1",
-            SyntaxNode::Int { .. },
+            "Int",
             Diagnostic::Error(DiagnosticError::ExtraToken {
                 kind: TokenKind::Let,
                 ..
@@ -275,33 +204,57 @@ mod test {
         check_tree_and_diagnostic_pattern!(
             parse,
             "let = 1; a", // 識別子が抜けている
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
+            "(Let Int Var)",
+            Diagnostic::Error(DiagnosticError::MissedToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "let - = 1; a",
+            "(Let Int Var)",
+            Diagnostic::Error(DiagnosticError::ExtraToken {
+                kind: TokenKind::Minus,
                 ..
-            },
+            })
             Diagnostic::Error(DiagnosticError::MissedToken { .. })
         );
         check_tree_and_diagnostic_pattern!(
             parse,
             "let a 1; a", // '=' が抜けている
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
-                ..
-            },
+            "(Let Int Var)",
             Diagnostic::Error(DiagnosticError::MissedToken { .. })
         );
-        // TODO: ';' の抜けを補完する
-        /* check_tree_and_diagnostic_pattern!(
+        check_tree_and_diagnostic_pattern!(
             parse,
             "let a = 1\n a", // ';' が抜けている
-            SyntaxNode::Let {
-                init_expr: box SyntaxNode::Int { .. },
-                body_expr: box SyntaxNode::Var { .. },
+            "(Let Int Var)",
+            Diagnostic::Error(DiagnosticError::MissedToken {
+                pos: Position(9),
                 ..
-            },
+            })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "let a = 1 + b // comment\n a * 2", // ';' が抜けている
+            "(Let (BinOp _ _ _) (BinOp _ _ _))",
+            Diagnostic::Error(DiagnosticError::MissedToken {
+                pos: Position(13),
+                ..
+            })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "let a = (1\n 2); a", // 括弧の中では改行で初期化式を終えない
+            "(Let (Paren (BinOp _ _ _)) _)",
             Diagnostic::Error(DiagnosticError::MissedToken { .. })
-        ); */
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "let g = f\n(1)", // ';' が抜けている
+            "(Let Var (Paren Int))",
+            Diagnostic::Error(DiagnosticError::MissedToken {
+                pos: Position(9),
+                ..
+            })
+        );
     }
 }
@@ -4,24 +4,42 @@ use super::*;
 primary_expr =
   | IDENT
   | NUM
+  | "true"
+  | "false"
   | "(" expr ")"
   ;
 */
 
 impl Parser {
     pub fn parse_primary_expr(&mut self, source: &Source, mut range: Range) -> ParseResult {
+        let token_start = range.start;
         let LexResult {
             token,
             remaining_range,
         } = lex(source, range);
-        range = remaining_range;
 
         let mut diagnostics = VecDeque::new();
 
+        if !PRIMARY_EXPR_BEGIN_TOKEN_KINDS.contains(&token.kind) {
+            // 式が書かれていない。トークンは消費せず、呼び出し元での回復に任せる
+            diagnostics.push_back(missing_expression_error(range.start));
+            return ParseResult {
+                node: SyntaxNode::missing(),
+                diagnostics,
+                remaining_range: range,
+            };
+        }
+        range = remaining_range;
+
         let mut node = match token.kind {
             TokenKind::Number => SyntaxNode::Int { token },
             TokenKind::Ident => SyntaxNode::Var { token },
+            TokenKind::True | TokenKind::False => SyntaxNode::Bool { token },
             TokenKind::OpenParen => {
+                let open_paren_range = Range {
+                    start: token_start,
+                    end: token_start + token.token_width,
+                };
                 let mut open_paren_token = token;
                 let (skipped_width, mut diagnostics_, range_) =
                     skip::until_not_error(source, range);
@@ -29,7 +47,9 @@ impl Parser {
                 range = range_;
                 open_paren_token.trailing_trivia_width += skipped_width;
 
+                let is_let_init = std::mem::replace(&mut self.is_let_init, false);
                 let mut inner_expr_result = self.parse_expr(source, range);
+                self.is_let_init = is_let_init;
                 range = inner_expr_result.remaining_range;
                 diagnostics.append(&mut inner_expr_result.diagnostics);
                 let inner_expr = inner_expr_result.node;
@@ -42,8 +62,14 @@ impl Parser {
                 let (close_paren_token, range_) = if token.kind == TokenKind::CloseParen {
                     (token, remaining_range)
                 } else {
-                    diagnostics
-                        .push_back(missed_token_error(range.start, vec![TokenKind::CloseParen]));
+                    diagnostics.push_back(missed_token_error_with_context(
+                        range.start,
+                        vec![TokenKind::CloseParen],
+                        Context {
+                            kind: ContextKind::Paren,
+                            range: open_paren_range,
+                        },
+                    ));
                     (
                         SyntaxToken {
                             kind: TokenKind::CloseParen,
@@ -58,11 +84,11 @@ impl Parser {
 
                 SyntaxNode::Paren {
                     open_paren_token,
-                    inner_expr: Box::new(inner_expr),
+                    inner_expr: ChildNode::new(inner_expr),
                     close_paren_token,
                 }
             }
-            _ => unreachable!(),
+            _ => unreachable!("checked above"),
         };
         let (skipped_width, mut diagnostics_, range) = skip::until_not_error(source, range);
         diagnostics.append(&mut diagnostics_);
@@ -90,84 +116,30 @@ mod test {
 
     #[test]
     fn test_normal_scenarios() {
-        check_tree_pattern!(parse, "1", SyntaxNode::Int { .. });
-        check_tree_pattern!(parse, "a", SyntaxNode::Var { .. });
-        check_tree_pattern!(
-            parse,
-            "(a)",
-            SyntaxNode::Paren {
-                inner_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
+        check_tree_pattern!(parse, "1", "Int");
+        check_tree_pattern!(parse, "a", "Var");
+        check_tree_pattern!(parse, "true", "Bool");
+        check_tree_pattern!(parse, "false ", "Bool");
+        check_tree_pattern!(parse, "(a)", "(Paren Var)");
     }
 
     #[test]
     fn test_normal_scenarios_with_trivia() {
-        check_tree_pattern!(parse, "1 ", SyntaxNode::Int { .. });
-        check_tree_pattern!(parse, "1 // comment ", SyntaxNode::Int { .. });
-        check_tree_pattern!(parse, "1 /* comment */ ", SyntaxNode::Int { .. });
-
-        check_tree_pattern!(parse, "a ", SyntaxNode::Var { .. });
-        check_tree_pattern!(parse, "a // comment ", SyntaxNode::Var { .. });
-        check_tree_pattern!(parse, "a /* comment */", SyntaxNode::Var { .. });
-
-        check_tree_pattern!(
-            parse,
-            "( a ) ",
-            SyntaxNode::Paren {
-                inner_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "( // comment\n a ) ",
-            SyntaxNode::Paren {
-                inner_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "( a // comment\n ) ",
-            SyntaxNode::Paren {
-                inner_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "( a ) // comment",
-            SyntaxNode::Paren {
-                inner_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "( /* comment */ a ) ",
-            SyntaxNode::Paren {
-                inner_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "( a /* comment */ ) ",
-            SyntaxNode::Paren {
-                inner_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
-        check_tree_pattern!(
-            parse,
-            "( a ) /* comment */ ",
-            SyntaxNode::Paren {
-                inner_expr: box SyntaxNode::Var { .. },
-                ..
-            }
-        );
+        check_tree_pattern!(parse, "1 ", "Int");
+        check_tree_pattern!(parse, "1 // comment ", "Int");
+        check_tree_pattern!(parse, "1 /* comment */ ", "Int");
+
+        check_tree_pattern!(parse, "a ", "Var");
+        check_tree_pattern!(parse, "a // comment ", "Var");
+        check_tree_pattern!(parse, "a /* comment */", "Var");
+
+        check_tree_pattern!(parse, "( a ) ", "(Paren Var)");
+        check_tree_pattern!(parse, "( // comment\n a ) ", "(Paren Var)");
+        check_tree_pattern!(parse, "( a // comment\n ) ", "(Paren Var)");
+        check_tree_pattern!(parse, "( a ) // comment", "(Paren Var)");
+        check_tree_pattern!(parse, "( /* comment */ a ) ", "(Paren Var)");
+        check_tree_pattern!(parse, "( a /* comment */ ) ", "(Paren Var)");
+        check_tree_pattern!(parse, "( a ) /* comment */ ", "(Paren Var)");
     }
 
     #[test]
@@ -175,21 +147,34 @@ mod test {
         check_tree_and_diagnostic_pattern!(
             parse,
             "1 @",
-            SyntaxNode::Int { .. },
+            "Int",
             Diagnostic::Error(DiagnosticError::UnknownToken { .. })
         );
         check_tree_and_diagnostic_pattern!(
             parse,
             "a @",
-            SyntaxNode::Var { .. },
+            "Var",
             Diagnostic::Error(DiagnosticError::UnknownToken { .. })
         );
 
         check_tree_and_diagnostic_pattern!(
             parse,
             "(a",
-            SyntaxNode::Paren { .. },
+            "(Paren _)",
             Diagnostic::Error(DiagnosticError::MissedToken { .. })
         );
     }
+
+    #[test]
+    fn test_not_primary_expr() {
+        // 呼び出し元で回復できるよう、トークンを消費しない
+        let source = Source::from_str("+ 1");
+        let result = parse(&source, source.range());
+        assert!(matches!(result.node, SyntaxNode::Missing { .. }));
+        assert!(matches!(
+            result.diagnostics.front(),
+            Some(Diagnostic::Error(DiagnosticError::MissingExpression { .. }))
+        ));
+        assert_eq!(result.remaining_range, source.range());
+    }
 }
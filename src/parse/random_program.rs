@@ -0,0 +1,218 @@
+use super::{ParseResult, Parser};
+use crate::lex::trivia::trivia_width;
+use crate::source::Source;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+// 構文に沿ったランダムなプログラムを作り、それを壊した入力でも構文解析器が約束を守るか確かめる
+// - 構文解析が panic しない
+// - 構文木から元のソースコードを復元できる
+// - 構文解析の後に読み残しが無い
+
+const IDENTS: [&str; 5] = ["a", "b", "x1", "foo", "let_"];
+
+// 変異で挿入する文字列。構文エラーや字句エラーを起こしやすいものを選ぶ
+const INSERTIONS: [&str; 30] = [
+    "@", "/*", "*/", "//", "\n", " ", "let", "let ", "=", ";", "(", ")", "+", "-", "*", "/", "1",
+    "a", "\t", "<", "!", "&", "|", "&&", "true", "if ", " then ", " else ", "fun ", "->",
+];
+
+const BINARY_OPERATORS: [&str; 12] = [
+    "+", "-", "*", "/", "==", "!=", "<", "<=", ">", ">=", "&&", "||",
+];
+
+fn gen_trivia(rng: &mut StdRng) -> String {
+    match rng.gen_range(0..10) {
+        0..=4 => " ".to_string(),
+        5 => String::new(),
+        6 => "\n".to_string(),
+        7 => " /* comment */ ".to_string(),
+        8 => " // comment\n".to_string(),
+        _ => "\n  ".to_string(),
+    }
+}
+
+fn gen_expr(rng: &mut StdRng, depth: usize) -> String {
+    let choice = if depth == 0 {
+        rng.gen_range(0..3)
+    } else {
+        rng.gen_range(0..11)
+    };
+    match choice {
+        0 => rng.gen_range(0..1000).to_string(),
+        1 => IDENTS.choose(rng).unwrap().to_string(),
+        2 => ["true", "false"].choose(rng).unwrap().to_string(),
+        3 | 4 => {
+            let op = BINARY_OPERATORS.choose(rng).unwrap();
+            format!(
+                "{}{}{op}{}{}",
+                gen_operand(rng, depth - 1),
+                gen_trivia(rng),
+                gen_trivia(rng),
+                gen_operand(rng, depth - 1)
+            )
+        }
+        5 => format!(
+            "({}{}{})",
+            gen_trivia(rng),
+            gen_expr(rng, depth - 1),
+            gen_trivia(rng)
+        ),
+        6 => format!(
+            "{}{}{}",
+            ["-", "+", "!"].choose(rng).unwrap(),
+            gen_trivia(rng),
+            gen_operand(rng, depth - 1)
+        ),
+        7 => format!(
+            "if {}{} then {}{} else {}{}",
+            gen_trivia(rng),
+            gen_expr(rng, depth - 1),
+            gen_trivia(rng),
+            gen_expr(rng, depth - 1),
+            gen_trivia(rng),
+            gen_expr(rng, depth - 1)
+        ),
+        8 => format!(
+            "fun {}{}->{}{}",
+            IDENTS.choose(rng).unwrap(),
+            gen_trivia(rng),
+            gen_trivia(rng),
+            gen_expr(rng, depth - 1)
+        ),
+        // let 式の初期化式では、改行の後の '(' は括弧の後に ';' が続かない限り ';' の抜けとみなされるので、間を空けない
+        9 => {
+            let callee = if rng.gen_bool(0.5) {
+                IDENTS.choose(rng).unwrap().to_string()
+            } else {
+                format!("({})", gen_expr(rng, depth - 1))
+            };
+            format!(
+                "{callee}({}{}{})",
+                gen_trivia(rng),
+                gen_expr(rng, depth - 1),
+                gen_trivia(rng)
+            )
+        }
+        _ => format!(
+            "let {}{}={}{};{}{}",
+            IDENTS.choose(rng).unwrap(),
+            gen_trivia(rng),
+            gen_trivia(rng),
+            gen_expr(rng, depth - 1),
+            gen_trivia(rng),
+            gen_expr(rng, depth - 1)
+        ),
+    }
+}
+
+// 二項演算の被演算子に let 式や if 式、無名関数は書けないので括弧で囲む
+fn gen_operand(rng: &mut StdRng, depth: usize) -> String {
+    let expr = gen_expr(rng, depth);
+    if expr.starts_with("let ") || expr.starts_with("if ") || expr.starts_with("fun ") {
+        format!("({expr})")
+    } else {
+        expr
+    }
+}
+
+pub fn gen_program(rng: &mut StdRng) -> String {
+    let depth = rng.gen_range(0..5);
+    format!("{}{}", gen_trivia(rng), gen_expr(rng, depth))
+}
+
+// 文字の挿入・削除・置き換えをランダムに何回か行う
+pub fn mutate(rng: &mut StdRng, program: &str) -> String {
+    let mut chars: Vec<char> = program.chars().collect();
+    for _ in 0..rng.gen_range(1..4) {
+        let pos = rng.gen_range(0..=chars.len());
+        match rng.gen_range(0..3) {
+            0 => {
+                let insertion = INSERTIONS.choose(rng).unwrap();
+                chars.splice(pos..pos, insertion.chars());
+            }
+            1 if pos < chars.len() => {
+                chars.remove(pos);
+            }
+            _ if pos < chars.len() => {
+                let insertion = INSERTIONS.choose(rng).unwrap();
+                chars.splice(pos..pos + 1, insertion.chars());
+            }
+            _ => (),
+        }
+    }
+    chars.into_iter().collect()
+}
+
+// 構文解析器の約束を確かめる。違反していれば入力と共に panic する
+fn check_properties(src: &str) {
+    let source = Source::from_str(src);
+    let result = std::panic::catch_unwind(|| {
+        let mut parser = Parser::new();
+        let mut range = source.range();
+        let leading_trivia_width = trivia_width(&source, range);
+        range.start.advance(leading_trivia_width);
+        let ParseResult {
+            mut node,
+            remaining_range,
+            ..
+        } = parser.parse_toplevel(&source, range);
+        node.extend_leading_trivia_width(leading_trivia_width);
+        (node, remaining_range)
+    });
+    let Ok((node, remaining_range)) = result else {
+        panic!("parse panicked on {src:?}");
+    };
+    assert!(
+        remaining_range.is_empty(),
+        "remaining range {remaining_range:?} on {src:?}"
+    );
+    assert_eq!(
+        node.full_width(),
+        source.range().width(),
+        "tree width differs from source on {src:?}"
+    );
+    assert_eq!(
+        node.text(&source),
+        source.get(&source.range()).iter().collect::<String>()
+    );
+
+    // トークンが隙間なく並び、全体でソースコードを覆う
+    let mut pos = source.range().start;
+    for (_, range) in node.tokens() {
+        assert_eq!(range.start, pos, "tokens are not contiguous on {src:?}");
+        pos = range.end;
+    }
+    assert_eq!(pos, source.range().end, "tokens do not cover {src:?}");
+}
+
+#[test]
+fn test_generated_programs() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..2000 {
+        let program = gen_program(&mut rng);
+        check_properties(&program);
+        // 構文に沿って作ったプログラムには診断が出ない
+        let (_, diagnostics) = super::parse(&Source::from_str(&program));
+        assert!(diagnostics.is_empty(), "{diagnostics:?} on {program:?}");
+    }
+}
+
+#[test]
+fn test_mutated_programs() {
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..5000 {
+        let program = gen_program(&mut rng);
+        check_properties(&mutate(&mut rng, &program));
+    }
+}
+
+#[test]
+fn test_found_by_mutation() {
+    check_properties("let 1");
+    check_properties("let 1 + a");
+    check_properties("/* a");
+    check_properties("1 /* a");
+    check_properties("let a = @");
+}
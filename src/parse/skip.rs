@@ -1,7 +1,7 @@
 use crate::diagnostic::{extra_token_error, unknown_token_error, Diagnostic};
 use crate::lex::{lex, LexResult};
 use crate::source::{Range, Source};
-use crate::syntax_node::{self, TokenKind};
+use crate::syntax_node::{self, TokenKind, EXPR_BEGIN_TOKEN_KINDS};
 use std::collections::VecDeque;
 
 // expected に含まれるトークンが出現するまでスキップする
@@ -38,16 +38,9 @@ pub fn until(
             }
         }
     }
-    (0, diagnostics, range)
+    (skipped_width, diagnostics, range)
 }
 
-const EXPR_BEGIN_TOKEN_KINDS: [TokenKind; 4] = [
-    TokenKind::Ident,
-    TokenKind::Number,
-    TokenKind::Let,
-    TokenKind::OpenParen,
-];
-
 pub fn until_expr_begin(source: &Source, range: Range) -> (usize, VecDeque<Diagnostic>, Range) {
     until(source, range, EXPR_BEGIN_TOKEN_KINDS.into_iter().collect())
 }
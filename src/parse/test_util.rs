@@ -1,13 +1,19 @@
+// 構文木の形を S 式で書いて確かめる
+// - Int, Var, Bool, Error, Missing はそのままの名前
+// - (BinOp <演算子> <左辺> <右辺>), (Unary <演算子> <被演算子>), (Let <初期化式> <本体>)
+// - (If <条件式> <then 節> <else 節>), (Lambda <本体>), (Call <関数> <実引数>), (Paren <中身>)
+// - 期待する形の中の "_" は任意の部分木や演算子にマッチする
+// e.g. "1 + a * (b)" は (BinOp + Int (BinOp * Var (Paren Var)))
 #[cfg(test)]
 macro_rules! check_tree_pattern {
-    ($parse:expr, $src:expr, $node_pat:pat) => {
+    ($parse:expr, $src:expr, $shape:expr) => {
         let source = crate::source::Source::from_str($src);
         let parse_result = $parse(&source, source.range());
         assert!(parse_result.remaining_range.is_empty());
         assert!(parse_result.diagnostics.is_empty());
-        std::assert_matches::assert_matches!(parse_result.node, $node_pat);
+        crate::parse::test_util::assert_shape(&parse_result.node, $shape);
 
-        let restored_str = parse_result.node.restore(&source);
+        let restored_str = parse_result.node.text(&source);
         assert_eq!(restored_str, format!("{}\n", $src));
     };
 }
@@ -17,18 +23,156 @@ pub(crate) use check_tree_pattern;
 
 #[cfg(test)]
 macro_rules! check_tree_and_diagnostic_pattern {
-    ($parse:expr, $src:expr, $node_pat:pat, $($diag_pat:pat)*) => {
+    ($parse:expr, $src:expr, $shape:expr, $($diag_pat:pat)*) => {
         let source = crate::source::Source::from_str($src);
         let mut parse_result = $parse(&source, source.range());
         assert!(parse_result.remaining_range.is_empty());
         $(
             assert!(!parse_result.diagnostics.is_empty());
             let diagnostic = parse_result.diagnostics.pop_front().unwrap();
-            std::assert_matches::assert_matches!(diagnostic, $diag_pat);
+            assert!(
+                matches!(diagnostic, $diag_pat),
+                "{diagnostic:?} does not match {}",
+                stringify!($diag_pat)
+            );
         )*
-        std::assert_matches::assert_matches!(parse_result.node, $node_pat);
+        crate::parse::test_util::assert_shape(&parse_result.node, $shape);
     };
 }
 
 #[cfg(test)]
 pub(crate) use check_tree_and_diagnostic_pattern;
+
+#[cfg(test)]
+use crate::syntax_node::SyntaxNode;
+
+#[cfg(test)]
+#[derive(Debug, PartialEq, Eq)]
+enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+}
+
+#[cfg(test)]
+impl Sexp {
+    fn parse(str: &str) -> Sexp {
+        let spaced = str.replace('(', " ( ").replace(')', " ) ");
+        let atoms: Vec<&str> = spaced.split_whitespace().collect();
+        let (sexp, rest) = Sexp::parse_from(&atoms);
+        assert!(rest.is_empty(), "extra input in {str:?}");
+        sexp
+    }
+
+    // 読んだ S 式と残りの入力を返す
+    fn parse_from<'a, 'b>(atoms: &'a [&'b str]) -> (Sexp, &'a [&'b str]) {
+        match atoms {
+            ["(", rest @ ..] => {
+                let mut list = vec![];
+                let mut rest = rest;
+                loop {
+                    match rest {
+                        [")", rest_ @ ..] => return (Sexp::List(list), rest_),
+                        [] => panic!("unclosed '('"),
+                        _ => {
+                            let (sexp, rest_) = Sexp::parse_from(rest);
+                            list.push(sexp);
+                            rest = rest_;
+                        }
+                    }
+                }
+            }
+            [")", ..] => panic!("unexpected ')'"),
+            [atom, rest @ ..] => (Sexp::Atom(atom.to_string()), rest),
+            [] => panic!("unexpected end of input"),
+        }
+    }
+
+    // self の "_" は任意の部分木にマッチする
+    fn matches(&self, actual: &Sexp) -> bool {
+        match (self, actual) {
+            (Sexp::Atom(atom), _) if atom == "_" => true,
+            (Sexp::Atom(expected), Sexp::Atom(actual)) => expected == actual,
+            (Sexp::List(expected), Sexp::List(actual)) => {
+                expected.len() == actual.len()
+                    && expected
+                        .iter()
+                        .zip(actual)
+                        .all(|(expected, actual)| expected.matches(actual))
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+pub fn shape(node: &SyntaxNode) -> String {
+    match node {
+        SyntaxNode::Int { .. } => "Int".to_string(),
+        SyntaxNode::Var { .. } => "Var".to_string(),
+        SyntaxNode::Bool { .. } => "Bool".to_string(),
+        SyntaxNode::Error { .. } => "Error".to_string(),
+        SyntaxNode::Missing { .. } => "Missing".to_string(),
+        SyntaxNode::BinOp {
+            lhs_expr,
+            binop_token,
+            rhs_expr,
+        } => format!(
+            "(BinOp {} {} {})",
+            binop_token.kind.text().unwrap(),
+            shape(lhs_expr),
+            shape(rhs_expr)
+        ),
+        SyntaxNode::Unary {
+            unop_token,
+            operand_expr,
+        } => format!(
+            "(Unary {} {})",
+            unop_token.kind.text().unwrap(),
+            shape(operand_expr)
+        ),
+        SyntaxNode::Let {
+            init_expr,
+            body_expr,
+            ..
+        } => format!("(Let {} {})", shape(init_expr), shape(body_expr)),
+        SyntaxNode::If {
+            cond_expr,
+            then_expr,
+            else_expr,
+            ..
+        } => format!(
+            "(If {} {} {})",
+            shape(cond_expr),
+            shape(then_expr),
+            shape(else_expr)
+        ),
+        SyntaxNode::Lambda { body_expr, .. } => format!("(Lambda {})", shape(body_expr)),
+        SyntaxNode::Call {
+            callee_expr,
+            arg_expr,
+            ..
+        } => format!("(Call {} {})", shape(callee_expr), shape(arg_expr)),
+        SyntaxNode::Paren { inner_expr, .. } => format!("(Paren {})", shape(inner_expr)),
+    }
+}
+
+#[cfg(test)]
+pub fn assert_shape(node: &SyntaxNode, expected: &str) {
+    let actual = shape(node);
+    assert!(
+        Sexp::parse(expected).matches(&Sexp::parse(&actual)),
+        "tree shape mismatch\n  expected: {expected}\n    actual: {actual}"
+    );
+}
+
+#[test]
+fn test_shape_matches() {
+    let matches =
+        |expected: &str, actual: &str| Sexp::parse(expected).matches(&Sexp::parse(actual));
+    assert!(matches("(BinOp + Int Var)", "(BinOp + Int Var)"));
+    assert!(matches("(BinOp _ Int _)", "(BinOp * Int (Paren Var))"));
+    assert!(matches("_", "(Let Int Var)"));
+    assert!(!matches("(BinOp + Int Var)", "(BinOp - Int Var)"));
+    assert!(!matches("(Paren Var)", "(Paren (Paren Var))"));
+    assert!(!matches("(Let Int)", "(Let Int Var)"));
+}
@@ -0,0 +1,96 @@
+use super::*;
+
+/*
+unary_expr =
+  | UNOP unary_expr
+  | call_expr
+  ;
+
+UNOP は UNARY_OPERATOR_TOKEN_KINDS のいずれか
+*/
+
+use crate::syntax_node::UNARY_OPERATOR_TOKEN_KINDS;
+
+impl Parser {
+    // 書かれていなければ Missing を返し、トークンは消費しない
+    pub fn parse_unary_expr(&mut self, source: &Source, range: Range) -> ParseResult {
+        let LexResult {
+            token,
+            remaining_range,
+        } = lex(source, range);
+        if PRIMARY_EXPR_BEGIN_TOKEN_KINDS.contains(&token.kind) {
+            return self.parse_call_expr(source, range);
+        }
+        if !UNARY_OPERATOR_TOKEN_KINDS.contains(&token.kind) {
+            let mut diagnostics = VecDeque::new();
+            diagnostics.push_back(missing_expression_error(range.start));
+            return ParseResult {
+                node: SyntaxNode::missing(),
+                diagnostics,
+                remaining_range: range,
+            };
+        }
+
+        let mut unop_token = token;
+        let (skipped_width, mut diagnostics, range) =
+            skip::until_not_error(source, remaining_range);
+        unop_token.trailing_trivia_width += skipped_width;
+
+        let mut operand_result = self.parse_unary_expr(source, range);
+        diagnostics.append(&mut operand_result.diagnostics);
+        ParseResult {
+            node: SyntaxNode::Unary {
+                unop_token,
+                operand_expr: ChildNode::new(operand_result.node),
+            },
+            diagnostics,
+            remaining_range: operand_result.remaining_range,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::test_util::*;
+    use super::{ParseResult, Parser, Range, Source};
+    use crate::diagnostic::{Diagnostic, DiagnosticError};
+
+    fn parse(source: &Source, range: Range) -> ParseResult {
+        let mut parser = Parser::new();
+        parser.parse_unary_expr(source, range)
+    }
+
+    #[test]
+    fn test_normal_scenarios() {
+        check_tree_pattern!(parse, "-1", "(Unary - Int)");
+        check_tree_pattern!(parse, "+a", "(Unary + Var)");
+        check_tree_pattern!(parse, "- -a", "(Unary - (Unary - Var))");
+        check_tree_pattern!(parse, "-(1)", "(Unary - (Paren Int))");
+        check_tree_pattern!(parse, "!!true", "(Unary ! (Unary ! Bool))");
+        check_tree_pattern!(parse, "1", "Int");
+    }
+
+    #[test]
+    fn test_normal_scenarios_with_trivia() {
+        check_tree_pattern!(parse, "- 1 ", "(Unary - Int)");
+        check_tree_pattern!(parse, "- // comment\n 1", "(Unary - Int)");
+        check_tree_pattern!(parse, "- /* comment */ 1", "(Unary - Int)");
+        check_tree_pattern!(parse, "-1 // comment", "(Unary - Int)");
+    }
+
+    #[test]
+    fn test_normal_scenarios_with_errors() {
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "- @ 1",
+            "(Unary - Int)",
+            Diagnostic::Error(DiagnosticError::UnknownToken { .. })
+        );
+        check_tree_and_diagnostic_pattern!(
+            parse,
+            "-",
+            "(Unary - Missing)",
+            Diagnostic::Error(DiagnosticError::MissingExpression { .. })
+        );
+    }
+}
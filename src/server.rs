@@ -1,8 +1,9 @@
 use crate::consts;
-use crate::diagnostic::Diagnostic;
 use crate::named_pipe::NamedPipeServer;
-use crate::parse;
-use crate::source::{Range, Source};
+use denvl::ast::Expr;
+use denvl::diagnostic::{json, postprocess, render, sarif, Diagnostic};
+use denvl::eval::{self, EvalError, Value};
+use denvl::source::Source;
 use std::collections::VecDeque;
 use std::fs::File;
 use std::io::Write;
@@ -14,7 +15,6 @@ pub fn run() {
     let mut error_file = File::options()
         .create(true)
         .read(false)
-        .write(true)
         .append(true)
         .open(&error)
         .unwrap();
@@ -51,80 +51,145 @@ pub fn run() {
             server.writeline("done".to_string()).unwrap();
             break;
         }
-        let path = PathBuf::from(line);
-        exec(&mut server, path);
+        let request = Request::from_line(&line);
+        exec(&mut server, &request);
         thread::sleep(time::Duration::from_millis(1000));
         server.writeline("done".to_string()).unwrap();
         thread::sleep(time::Duration::from_millis(1000));
     }
 }
 
-fn exec(server: &mut NamedPipeServer, path: PathBuf) {
-    let source = Source::new(path.as_path()).expect("fail to read file");
-    let (syntax_node, diagnostics) = parse::parse(&source);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageFormat {
+    Human,
+    Json,
+    Sarif,
+}
 
-    print_diagnostics(server, path.to_str().unwrap(), &source, diagnostics).unwrap();
+// クライアントからの実行要求
+// オプションを "key=value" 形式で並べ、最後にファイルパスを置いてタブ区切りで 1 行に収める
+pub struct Request {
+    pub path: PathBuf,
+    pub color: bool,
+    pub message_format: MessageFormat,
+    // 表示する診断の最大数
+    pub max_errors: Option<usize>,
+}
 
-    server
-        .writeline(format!("parsed tree: {syntax_node:?}"))
-        .unwrap();
+impl Request {
+    pub fn to_line(&self) -> String {
+        let mut fields = vec![
+            format!("color={}", if self.color { "always" } else { "never" }),
+            format!(
+                "message-format={}",
+                match self.message_format {
+                    MessageFormat::Human => "human",
+                    MessageFormat::Json => "json",
+                    MessageFormat::Sarif => "sarif",
+                }
+            ),
+        ];
+        if let Some(max_errors) = self.max_errors {
+            fields.push(format!("max-errors={max_errors}"));
+        }
+        fields.push(self.path.display().to_string());
+        fields.join("\t")
+    }
+
+    pub fn from_line(line: &str) -> Request {
+        let mut fields: Vec<&str> = line.split('\t').collect();
+        let path = PathBuf::from(fields.pop().unwrap_or_default());
+        let mut request = Request {
+            path,
+            color: false,
+            message_format: MessageFormat::Human,
+            max_errors: None,
+        };
+        for field in fields {
+            match field.split_once('=') {
+                Some(("color", value)) => request.color = value == "always",
+                Some(("message-format", "json")) => request.message_format = MessageFormat::Json,
+                Some(("message-format", "sarif")) => request.message_format = MessageFormat::Sarif,
+                Some(("max-errors", value)) => request.max_errors = value.parse().ok(),
+                _ => (),
+            }
+        }
+        request
+    }
+}
+
+fn exec(server: &mut NamedPipeServer, request: &Request) {
+    let source = Source::new(request.path.as_path()).expect("fail to read file");
+    let (syntax_node, diagnostics) = denvl::parse(&source);
+
+    let has_error = !diagnostics.is_empty();
+
+    for line in render_diagnostics(request, &source, diagnostics) {
+        server.writeline(line).unwrap();
+    }
+
+    // JSON や SARIF の出力はそのまま機械的に読まれるので、評価結果は混ぜない
+    if request.message_format == MessageFormat::Human && !has_error {
+        let result = eval::eval(&source, Expr::root(&syntax_node));
+        let filename = request.path.to_str().unwrap();
+        server
+            .writeline(render_eval_result(filename, &source, &result))
+            .unwrap();
+    }
 }
 
-fn print_diagnostics(
-    server: &mut NamedPipeServer,
+// e.g. "42", "runtime error: division by zero at main.denvl:1:1"
+pub fn render_eval_result(
     filename: &str,
     source: &Source,
-    mut diagnostics: VecDeque<Diagnostic>,
-) -> Result<(), std::io::Error> {
-    diagnostics.make_contiguous().sort_by_key(|lhs| lhs.pos());
-    let mut line = 0;
-    let mut column = 1;
-    let mut range = source.range();
-
-    while !range.is_empty() {
-        let c = source.at(range.start);
-        let diagnostic = diagnostics.front();
-        if let Some(diagnostic) = diagnostic {
-            if diagnostic.pos() == range.start {
-                print_diagnostic(server, filename, source, line, column, diagnostic)?;
-                diagnostics.pop_front();
-            }
-            if c == '\n' {
-                line += 1;
-                column = 0;
-            } else {
-                column += 1;
-            }
-            range.start.advance(1);
-        } else {
-            break;
+    result: &Result<Value, EvalError>,
+) -> String {
+    match result {
+        Ok(value) => value.to_string(),
+        Err(e) => {
+            let (line, column) = source.line_column(e.range().start);
+            format!(
+                "runtime error: {} at {filename}:{line}:{column}",
+                e.message()
+            )
         }
     }
-    Ok(())
 }
 
-fn print_diagnostic(
-    server: &mut NamedPipeServer,
-    filename: &str,
+pub fn render_diagnostics(
+    request: &Request,
     source: &Source,
-    line: usize,
-    column: usize,
-    diagnostic: &Diagnostic,
-) -> Result<(), std::io::Error> {
-    server.writeline(format!(
-        "error at {}({}:{}) {}",
-        filename,
-        line,
-        column,
-        diagnostic.make_msg()
-    ))?;
-    let end = diagnostic.pos();
-    let mut start = end;
-    start.backward(column);
-    server.writeline(format!(
-        "> {}",
-        source.get(&Range { start, end }).iter().collect::<String>()
-    ))?;
-    server.writeline(format!("{}^", " ".repeat(column + 2)))?;
-    Ok(())
+    diagnostics: VecDeque<Diagnostic>,
+) -> Vec<String> {
+    let filename = request.path.to_str().unwrap();
+    let mut diagnostics = postprocess::postprocess(diagnostics);
+    let omitted_count = postprocess::limit(&mut diagnostics, request.max_errors);
+
+    // SARIF は全ての診断情報を 1 つのログにまとめる
+    if request.message_format == MessageFormat::Sarif {
+        return vec![sarif::render(filename, source, &diagnostics)];
+    }
+
+    let mut lines = vec![];
+    for diagnostic in diagnostics {
+        match request.message_format {
+            MessageFormat::Human => {
+                lines.append(&mut render::render(
+                    filename,
+                    source,
+                    &diagnostic,
+                    request.color,
+                ));
+                lines.push(String::new());
+            }
+            MessageFormat::Json => lines.push(json::render(filename, source, &diagnostic)),
+            MessageFormat::Sarif => unreachable!(),
+        }
+    }
+    if omitted_count > 0 && request.message_format == MessageFormat::Human {
+        lines.push(format!(
+            "note: {omitted_count} more error(s) not shown. use --max-errors to change the limit"
+        ));
+    }
+    lines
 }
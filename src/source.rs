@@ -12,22 +12,51 @@ use std::path::Path;
 // Source の内部実装メモ
 // - Source は行の列とみなせる
 
+/// 構文解析の対象となるソースコード
+///
+/// 位置 ([`Position`]) はソースコード先頭からの文字数 (バイト数ではない) で表す。
+/// 全ての行は改行で終わるものとして持つので、最終行に改行が無いファイルも末尾に改行を補う。
+/// 行末の "\r\n" は '\n' として持つ。元のファイルでの位置は [`Source::original_offset`] で得る
 pub struct Source {
     pub buffer: Vec<char>,
+    // 元のファイルで "\r\n" だった行末 (buffer 上の '\n') の位置。昇順
+    crlf_line_ends: Vec<Position>,
+    // 元のファイルの文字数
+    original_len: usize,
 }
 
 impl Source {
     pub fn new(path: &Path) -> Result<Self, std::io::Error> {
         let src = std::fs::read_to_string(path)?;
-        let buffer = src
-            .lines()
-            .flat_map(|line| {
-                let mut line: Vec<_> = line.chars().collect();
-                line.push('\n');
-                line
-            })
-            .collect();
-        Ok(Source { buffer })
+        Ok(Source::from_str(&src))
+    }
+
+    // 全ての行は改行で終わるものとして持つ
+    // 失敗しないので FromStr は実装しない
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(str: &str) -> Self {
+        let mut buffer = vec![];
+        let mut crlf_line_ends = vec![];
+        // str.lines() と同じく "\n" と "\r\n" で行を分ける
+        for line in str.split_inclusive('\n') {
+            let (line, crlf) = match line.strip_suffix('\n') {
+                Some(line) => match line.strip_suffix('\r') {
+                    Some(line) => (line, true),
+                    None => (line, false),
+                },
+                None => (line, false),
+            };
+            buffer.extend(line.chars());
+            if crlf {
+                crlf_line_ends.push(Position(buffer.len()));
+            }
+            buffer.push('\n');
+        }
+        Self {
+            buffer,
+            crlf_line_ends,
+            original_len: str.chars().count(),
+        }
     }
 
     // 事前条件: check_pos_validity(pos)
@@ -50,6 +79,71 @@ impl Source {
     pub fn get(&self, range: &Range) -> &[char] {
         &self.buffer[range.start.0..range.end.0]
     }
+
+    // pos の (行番号, 列番号) を 1 origin で返す
+    // ファイル末尾を指す位置は最終行の行末とみなす
+    pub fn line_column(&self, pos: Position) -> (usize, usize) {
+        let end = pos.0.min(self.buffer.len().saturating_sub(1));
+        let mut line = 1;
+        let mut column = 1;
+        for c in &self.buffer[..end] {
+            if *c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+
+    // pos に対応する、元のファイル先頭からの文字数
+    // 落とした '\r' の分だけずらす。行末の '\n' は元の改行 ("\r\n" なら '\r') の位置に、補った改行は元のファイルの末尾に対応させる
+    pub fn original_offset(&self, pos: Position) -> usize {
+        let dropped = self.crlf_line_ends.partition_point(|end| *end < pos);
+        (pos.0 + dropped).min(self.original_len)
+    }
+
+    // range の部分を元のファイルの綴りで返す
+    // "\r\n" だった行末は "\r\n" に戻し、末尾に補った改行は含めない
+    pub fn original_text(&self, range: &Range) -> String {
+        let added_newline = self.buffer.len() + self.crlf_line_ends.len() > self.original_len;
+        let end = if added_newline && range.end.0 == self.buffer.len() {
+            range.end.0 - 1
+        } else {
+            range.end.0
+        };
+        let first = self
+            .crlf_line_ends
+            .partition_point(|pos| *pos < range.start);
+        let mut crlf_line_ends = self.crlf_line_ends[first..].iter().peekable();
+        let mut text = String::new();
+        for pos in range.start.0..end.max(range.start.0) {
+            if crlf_line_ends.next_if(|crlf| crlf.0 == pos).is_some() {
+                text.push('\r');
+            }
+            text.push(self.buffer[pos]);
+        }
+        text
+    }
+
+    // line 行目 (1 origin) の範囲を返す。末尾の改行は含まない
+    pub fn line_range(&self, line: usize) -> Range {
+        let mut start = Position::start();
+        for _ in 1..line {
+            while self.check_pos_validity(start) && self.at(start) != '\n' {
+                start.advance(1);
+            }
+            if self.check_pos_validity(start) {
+                start.advance(1);
+            }
+        }
+        let mut end = start;
+        while self.check_pos_validity(end) && self.at(end) != '\n' {
+            end.advance(1);
+        }
+        Range { start, end }
+    }
 }
 
 // 0 origin
@@ -128,19 +222,3 @@ pub fn starts_with(source: &Source, str: &str, range: &Range) -> bool {
 pub fn match_(source: &Source, str: &str, range: &Range) -> bool {
     starts_with(source, str, range) && str.len() == range.width()
 }
-
-#[cfg(test)]
-impl Source {
-    pub fn from_str(str: &str) -> Self {
-        Self {
-            buffer: str
-                .lines()
-                .flat_map(|line| {
-                    let mut line: Vec<_> = line.chars().collect();
-                    line.push('\n');
-                    line
-                })
-                .collect(),
-        }
-    }
-}
@@ -1,6 +1,17 @@
-#[cfg(test)]
+pub mod dump;
+pub mod green;
+pub mod red;
+pub mod visit;
+
 use crate::source::{Position, Range, Source};
+use visit::{MutVisitor, Visitor};
 
+/// ロスレスな構文木
+///
+/// 各トークン ([`SyntaxToken`]) は自身の前後の trivia の幅を持ち、構文木全体でソースコードを隙間なく覆う。
+/// ノードは絶対位置を持たないので、位置が必要なときは [`SyntaxNode::tokens`] や [`SyntaxNode::descendants`] を使う。
+/// 子ノードは [`ChildNode`] として自身の幅を持つので、[`SyntaxNode::full_width`] は部分木を辿らない。
+/// 構文エラーで抜けていたトークンは幅 0 のトークンとして、抜けていた式は [`SyntaxNode::Missing`] として表す
 #[derive(Debug)]
 pub enum SyntaxNode {
     Int {
@@ -9,139 +20,684 @@ pub enum SyntaxNode {
     Var {
         token: SyntaxToken,
     },
+    // true または false
+    Bool {
+        token: SyntaxToken,
+    },
     Let {
         let_token: SyntaxToken,
         ident_token: SyntaxToken,
         equal_token: SyntaxToken,
-        init_expr: Box<SyntaxNode>,
+        init_expr: ChildNode,
         semicolon_token: SyntaxToken,
-        body_expr: Box<SyntaxNode>,
+        body_expr: ChildNode,
+    },
+    // 条件式。e.g. if a < b then a else b
+    If {
+        if_token: SyntaxToken,
+        cond_expr: ChildNode,
+        then_token: SyntaxToken,
+        then_expr: ChildNode,
+        else_token: SyntaxToken,
+        else_expr: ChildNode,
+    },
+    // 無名関数。e.g. fun x -> x + 1
+    Lambda {
+        fun_token: SyntaxToken,
+        param_token: SyntaxToken,
+        arrow_token: SyntaxToken,
+        body_expr: ChildNode,
+    },
+    // 関数適用。e.g. f(1)
+    Call {
+        callee_expr: ChildNode,
+        open_paren_token: SyntaxToken,
+        arg_expr: ChildNode,
+        close_paren_token: SyntaxToken,
     },
     BinOp {
-        lhs_expr: Box<SyntaxNode>,
+        lhs_expr: ChildNode,
         binop_token: SyntaxToken,
-        rhs_expr: Box<SyntaxNode>,
+        rhs_expr: ChildNode,
+    },
+    // 前置演算子。e.g. -a
+    Unary {
+        unop_token: SyntaxToken,
+        operand_expr: ChildNode,
     },
     Paren {
         open_paren_token: SyntaxToken,
-        inner_expr: Box<SyntaxNode>,
+        inner_expr: ChildNode,
         close_paren_token: SyntaxToken,
     },
     Error {
         token: SyntaxToken,
     },
+    // 書かれていない式。幅 0 のトークンを持ち、読み飛ばした部分は trivia として保持する
+    Missing {
+        token: SyntaxToken,
+    },
 }
 
 impl SyntaxNode {
+    pub fn missing() -> Self {
+        SyntaxNode::Missing {
+            token: SyntaxToken::make_empty(TokenKind::Error),
+        }
+    }
+
     pub fn extend_leading_trivia_width(&mut self, n: usize) {
+        FirstTokenMut(|token: &mut SyntaxToken| token.leading_trivia_width += n)
+            .visit_node_mut(self)
+    }
+
+    pub fn extend_trailing_trivia_width(&mut self, n: usize) {
+        LastTokenMut(|token: &mut SyntaxToken| token.trailing_trivia_width += n)
+            .visit_node_mut(self)
+    }
+
+    pub fn trailing_trivia_width(&self) -> usize {
+        let mut width = 0;
+        LastToken(|token: &SyntaxToken| width = token.trailing_trivia_width).visit_node(self);
+        width
+    }
+
+    // 子をソースコード上の出現順に返す
+    pub fn children(&self) -> Children<'_> {
+        use SyntaxElement as E;
+        use SyntaxNode::*;
+        let elements = match self {
+            Int { token }
+            | Var { token }
+            | Bool { token }
+            | Error { token }
+            | Missing { token } => [Some(E::Token(token)), None, None, None, None, None],
+            Let {
+                let_token,
+                ident_token,
+                equal_token,
+                init_expr,
+                semicolon_token,
+                body_expr,
+            } => [
+                Some(E::Token(let_token)),
+                Some(E::Token(ident_token)),
+                Some(E::Token(equal_token)),
+                Some(E::Node(init_expr)),
+                Some(E::Token(semicolon_token)),
+                Some(E::Node(body_expr)),
+            ],
+            If {
+                if_token,
+                cond_expr,
+                then_token,
+                then_expr,
+                else_token,
+                else_expr,
+            } => [
+                Some(E::Token(if_token)),
+                Some(E::Node(cond_expr)),
+                Some(E::Token(then_token)),
+                Some(E::Node(then_expr)),
+                Some(E::Token(else_token)),
+                Some(E::Node(else_expr)),
+            ],
+            Lambda {
+                fun_token,
+                param_token,
+                arrow_token,
+                body_expr,
+            } => [
+                Some(E::Token(fun_token)),
+                Some(E::Token(param_token)),
+                Some(E::Token(arrow_token)),
+                Some(E::Node(body_expr)),
+                None,
+                None,
+            ],
+            Call {
+                callee_expr,
+                open_paren_token,
+                arg_expr,
+                close_paren_token,
+            } => [
+                Some(E::Node(callee_expr)),
+                Some(E::Token(open_paren_token)),
+                Some(E::Node(arg_expr)),
+                Some(E::Token(close_paren_token)),
+                None,
+                None,
+            ],
+            BinOp {
+                lhs_expr,
+                binop_token,
+                rhs_expr,
+            } => [
+                Some(E::Node(lhs_expr)),
+                Some(E::Token(binop_token)),
+                Some(E::Node(rhs_expr)),
+                None,
+                None,
+                None,
+            ],
+            Unary {
+                unop_token,
+                operand_expr,
+            } => [
+                Some(E::Token(unop_token)),
+                Some(E::Node(operand_expr)),
+                None,
+                None,
+                None,
+                None,
+            ],
+            Paren {
+                open_paren_token,
+                inner_expr,
+                close_paren_token,
+            } => [
+                Some(E::Token(open_paren_token)),
+                Some(E::Node(inner_expr)),
+                Some(E::Token(close_paren_token)),
+                None,
+                None,
+                None,
+            ],
+        };
+        elements.into_iter().flatten()
+    }
+
+    // trivia を含めた幅。子ノードの幅は ChildNode が持っているので、直下の子を足すだけで済む
+    pub fn full_width(&self) -> usize {
         use SyntaxNode::*;
         match self {
-            Int { token } => token.leading_trivia_width += n,
-            Var { token } => token.leading_trivia_width += n,
-            Let { let_token, .. } => let_token.leading_trivia_width += n,
-            BinOp { lhs_expr, .. } => lhs_expr.extend_leading_trivia_width(n),
+            Int { token }
+            | Var { token }
+            | Bool { token }
+            | Error { token }
+            | Missing { token } => token.full_width(),
+            Let {
+                let_token,
+                ident_token,
+                equal_token,
+                init_expr,
+                semicolon_token,
+                body_expr,
+            } => {
+                let_token.full_width()
+                    + ident_token.full_width()
+                    + equal_token.full_width()
+                    + init_expr.full_width()
+                    + semicolon_token.full_width()
+                    + body_expr.full_width()
+            }
+            If {
+                if_token,
+                cond_expr,
+                then_token,
+                then_expr,
+                else_token,
+                else_expr,
+            } => {
+                if_token.full_width()
+                    + cond_expr.full_width()
+                    + then_token.full_width()
+                    + then_expr.full_width()
+                    + else_token.full_width()
+                    + else_expr.full_width()
+            }
+            Lambda {
+                fun_token,
+                param_token,
+                arrow_token,
+                body_expr,
+            } => {
+                fun_token.full_width()
+                    + param_token.full_width()
+                    + arrow_token.full_width()
+                    + body_expr.full_width()
+            }
+            Call {
+                callee_expr,
+                open_paren_token,
+                arg_expr,
+                close_paren_token,
+            } => {
+                callee_expr.full_width()
+                    + open_paren_token.full_width()
+                    + arg_expr.full_width()
+                    + close_paren_token.full_width()
+            }
+            BinOp {
+                lhs_expr,
+                binop_token,
+                rhs_expr,
+            } => lhs_expr.full_width() + binop_token.full_width() + rhs_expr.full_width(),
+            Unary {
+                unop_token,
+                operand_expr,
+            } => unop_token.full_width() + operand_expr.full_width(),
             Paren {
-                open_paren_token, ..
-            } => open_paren_token.leading_trivia_width += n,
-            Error { token } => token.leading_trivia_width += n,
+                open_paren_token,
+                inner_expr,
+                close_paren_token,
+            } => {
+                open_paren_token.full_width()
+                    + inner_expr.full_width()
+                    + close_paren_token.full_width()
+            }
         }
     }
 
-    pub fn extend_trailing_trivia_width(&mut self, n: usize) {
+    // 子ノードの中身を書き換えた後に、それらの幅を計算し直す
+    fn update_child_widths(&mut self) {
         use SyntaxNode::*;
         match self {
-            Int { token } => token.trailing_trivia_width += n,
-            Var { token } => token.trailing_trivia_width += n,
-            Let { body_expr, .. } => body_expr.extend_trailing_trivia_width(n),
-            BinOp { rhs_expr, .. } => rhs_expr.extend_trailing_trivia_width(n),
-            Paren {
-                close_paren_token, ..
-            } => close_paren_token.trailing_trivia_width += n,
-            Error { token } => token.trailing_trivia_width += n,
+            Int { .. } | Var { .. } | Bool { .. } | Error { .. } | Missing { .. } => {}
+            Let {
+                init_expr,
+                body_expr,
+                ..
+            } => {
+                init_expr.update_full_width();
+                body_expr.update_full_width();
+            }
+            If {
+                cond_expr,
+                then_expr,
+                else_expr,
+                ..
+            } => {
+                cond_expr.update_full_width();
+                then_expr.update_full_width();
+                else_expr.update_full_width();
+            }
+            Lambda { body_expr, .. } => body_expr.update_full_width(),
+            Call {
+                callee_expr,
+                arg_expr,
+                ..
+            } => {
+                callee_expr.update_full_width();
+                arg_expr.update_full_width();
+            }
+            BinOp {
+                lhs_expr, rhs_expr, ..
+            } => {
+                lhs_expr.update_full_width();
+                rhs_expr.update_full_width();
+            }
+            Unary { operand_expr, .. } => operand_expr.update_full_width(),
+            Paren { inner_expr, .. } => inner_expr.update_full_width(),
         }
     }
 
-    #[cfg(test)]
-    pub fn restore(&self, source: &Source) -> String {
-        let (pos, result) = string_from_node(source, Position::start(), self);
-        assert_eq!(pos, source.range().end);
-        result
+    // 構文木が表すソースコードをコメントや空白も含めてそのまま返す
+    // self はソースコード全体を解析した構文木とする。部分木には text_at を使う
+    pub fn text(&self, source: &Source) -> String {
+        self.text_at(source, Position::start())
+    }
+
+    // self が offset から始まるときの text
+    pub fn text_at(&self, source: &Source, offset: Position) -> String {
+        let range = Range {
+            start: offset,
+            end: offset + self.full_width(),
+        };
+        source.get(&range).iter().collect()
+    }
+
+    // 全てのトークンを、trivia を含めた絶対位置の範囲とともに出現順に返す
+    // self はソースコード全体を解析した構文木とする。部分木には tokens_at を使う
+    pub fn tokens(&self) -> Tokens<'_> {
+        self.tokens_at(Position::start())
+    }
+
+    // self が offset から始まるときの tokens
+    pub fn tokens_at(&self, offset: Position) -> Tokens<'_> {
+        Tokens {
+            stack: vec![SyntaxElement::Node(self)],
+            pos: offset,
+        }
+    }
+
+    // self を含む全ての子孫ノードを、絶対位置の範囲とともに行きがけ順で返す
+    pub fn descendants(&self) -> Descendants<'_> {
+        self.descendants_at(Position::start())
+    }
+
+    // self が offset から始まるときの descendants
+    pub fn descendants_at(&self, offset: Position) -> Descendants<'_> {
+        Descendants {
+            stack: vec![(self, offset)],
+        }
     }
 }
 
-#[cfg(test)]
-fn string_from_node(source: &Source, pos: Position, node: &SyntaxNode) -> (Position, String) {
-    use SyntaxNode::*;
-    match node {
-        Int { token } | Var { token } | Error { token } => string_from_token(source, pos, token),
-        Let {
-            let_token,
-            ident_token,
-            equal_token,
-            init_expr,
-            semicolon_token,
-            body_expr,
-        } => {
-            let mut result = String::new();
-            let (pos, let_str) = string_from_token(source, pos, let_token);
-            result += &let_str;
-            let (pos, ident_str) = string_from_token(source, pos, ident_token);
-            result += &ident_str;
-            let (pos, equal_str) = string_from_token(source, pos, equal_token);
-            result += &equal_str;
-            let (pos, init_str) = string_from_node(source, pos, init_expr);
-            result += &init_str;
-            let (pos, semicolon_str) = string_from_token(source, pos, semicolon_token);
-            result += &semicolon_str;
-            let (pos, body_str) = string_from_node(source, pos, body_expr);
-            result += &body_str;
-            (pos, result)
+/// 子ノード
+///
+/// 部分木の trivia を含めた幅を持っておき、位置の計算で部分木を辿らずに済むようにする。
+/// 幅がずれないように、中身は [`MutVisitor`] を通してしか書き換えられない
+pub struct ChildNode {
+    node: Box<SyntaxNode>,
+    full_width: usize,
+}
+
+impl ChildNode {
+    pub fn new(node: SyntaxNode) -> Self {
+        let full_width = node.full_width();
+        ChildNode {
+            node: Box::new(node),
+            full_width,
         }
-        BinOp {
-            lhs_expr,
-            binop_token,
-            rhs_expr,
-        } => {
-            let mut result = String::new();
-            let (pos, lhs_str) = string_from_node(source, pos, lhs_expr);
-            result += &lhs_str;
-            let (pos, binop_str) = string_from_token(source, pos, binop_token);
-            result += &binop_str;
-            let (pos, rhs_expr) = string_from_node(source, pos, rhs_expr);
-            result += &rhs_expr;
-            (pos, result)
+    }
+
+    pub fn full_width(&self) -> usize {
+        self.full_width
+    }
+
+    fn update_full_width(&mut self) {
+        self.full_width = self.node.full_width();
+    }
+}
+
+impl std::ops::Deref for ChildNode {
+    type Target = SyntaxNode;
+
+    fn deref(&self) -> &SyntaxNode {
+        &self.node
+    }
+}
+
+impl std::fmt::Debug for ChildNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+// 先頭のトークンだけに f を適用する
+struct FirstTokenMut<F>(F);
+
+impl<F: FnMut(&mut SyntaxToken)> MutVisitor for FirstTokenMut<F> {
+    fn visit_let_mut(
+        &mut self,
+        let_token: &mut SyntaxToken,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+    ) {
+        self.visit_token_mut(let_token)
+    }
+
+    fn visit_if_mut(
+        &mut self,
+        if_token: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+    ) {
+        self.visit_token_mut(if_token)
+    }
+
+    fn visit_lambda_mut(
+        &mut self,
+        fun_token: &mut SyntaxToken,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+    ) {
+        self.visit_token_mut(fun_token)
+    }
+
+    fn visit_call_mut(
+        &mut self,
+        callee_expr: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+    ) {
+        self.visit_node_mut(callee_expr)
+    }
+
+    fn visit_binop_mut(
+        &mut self,
+        lhs_expr: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+    ) {
+        self.visit_node_mut(lhs_expr)
+    }
+
+    fn visit_unary_mut(&mut self, unop_token: &mut SyntaxToken, _: &mut SyntaxNode) {
+        self.visit_token_mut(unop_token)
+    }
+
+    fn visit_paren_mut(
+        &mut self,
+        open_paren_token: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+    ) {
+        self.visit_token_mut(open_paren_token)
+    }
+
+    fn visit_token_mut(&mut self, token: &mut SyntaxToken) {
+        (self.0)(token)
+    }
+}
+
+// 末尾のトークンだけに f を適用する
+struct LastTokenMut<F>(F);
+
+impl<F: FnMut(&mut SyntaxToken)> MutVisitor for LastTokenMut<F> {
+    fn visit_let_mut(
+        &mut self,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+        body_expr: &mut SyntaxNode,
+    ) {
+        self.visit_node_mut(body_expr)
+    }
+
+    fn visit_if_mut(
+        &mut self,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+        else_expr: &mut SyntaxNode,
+    ) {
+        self.visit_node_mut(else_expr)
+    }
+
+    fn visit_lambda_mut(
+        &mut self,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxToken,
+        body_expr: &mut SyntaxNode,
+    ) {
+        self.visit_node_mut(body_expr)
+    }
+
+    fn visit_call_mut(
+        &mut self,
+        _: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+        close_paren_token: &mut SyntaxToken,
+    ) {
+        self.visit_token_mut(close_paren_token)
+    }
+
+    fn visit_binop_mut(
+        &mut self,
+        _: &mut SyntaxNode,
+        _: &mut SyntaxToken,
+        rhs_expr: &mut SyntaxNode,
+    ) {
+        self.visit_node_mut(rhs_expr)
+    }
+
+    fn visit_unary_mut(&mut self, _: &mut SyntaxToken, operand_expr: &mut SyntaxNode) {
+        self.visit_node_mut(operand_expr)
+    }
+
+    fn visit_paren_mut(
+        &mut self,
+        _: &mut SyntaxToken,
+        _: &mut SyntaxNode,
+        close_paren_token: &mut SyntaxToken,
+    ) {
+        self.visit_token_mut(close_paren_token)
+    }
+
+    fn visit_token_mut(&mut self, token: &mut SyntaxToken) {
+        (self.0)(token)
+    }
+}
+
+// LastTokenMut の読み取り専用版
+struct LastToken<F>(F);
+
+impl<F: FnMut(&SyntaxToken)> Visitor for LastToken<F> {
+    fn visit_let(
+        &mut self,
+        _: &SyntaxToken,
+        _: &SyntaxToken,
+        _: &SyntaxToken,
+        _: &SyntaxNode,
+        _: &SyntaxToken,
+        body_expr: &SyntaxNode,
+    ) {
+        self.visit_node(body_expr)
+    }
+
+    fn visit_if(
+        &mut self,
+        _: &SyntaxToken,
+        _: &SyntaxNode,
+        _: &SyntaxToken,
+        _: &SyntaxNode,
+        _: &SyntaxToken,
+        else_expr: &SyntaxNode,
+    ) {
+        self.visit_node(else_expr)
+    }
+
+    fn visit_lambda(
+        &mut self,
+        _: &SyntaxToken,
+        _: &SyntaxToken,
+        _: &SyntaxToken,
+        body_expr: &SyntaxNode,
+    ) {
+        self.visit_node(body_expr)
+    }
+
+    fn visit_call(
+        &mut self,
+        _: &SyntaxNode,
+        _: &SyntaxToken,
+        _: &SyntaxNode,
+        close_paren_token: &SyntaxToken,
+    ) {
+        self.visit_token(close_paren_token)
+    }
+
+    fn visit_binop(&mut self, _: &SyntaxNode, _: &SyntaxToken, rhs_expr: &SyntaxNode) {
+        self.visit_node(rhs_expr)
+    }
+
+    fn visit_unary(&mut self, _: &SyntaxToken, operand_expr: &SyntaxNode) {
+        self.visit_node(operand_expr)
+    }
+
+    fn visit_paren(&mut self, _: &SyntaxToken, _: &SyntaxNode, close_paren_token: &SyntaxToken) {
+        self.visit_token(close_paren_token)
+    }
+
+    fn visit_token(&mut self, token: &SyntaxToken) {
+        (self.0)(token)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SyntaxElement<'a> {
+    Node(&'a SyntaxNode),
+    Token(&'a SyntaxToken),
+}
+
+impl SyntaxElement<'_> {
+    pub fn full_width(&self) -> usize {
+        match self {
+            SyntaxElement::Node(node) => node.full_width(),
+            SyntaxElement::Token(token) => token.full_width(),
         }
-        Paren {
-            open_paren_token,
-            inner_expr,
-            close_paren_token,
-        } => {
-            let mut result = String::new();
-            let (pos, open_paren_str) = string_from_token(source, pos, open_paren_token);
-            result += &open_paren_str;
-            let (pos, inner_str) = string_from_node(source, pos, inner_expr);
-            result += &inner_str;
-            let (pos, close_paren_str) = string_from_token(source, pos, close_paren_token);
-            result += &close_paren_str;
-            (pos, result)
+    }
+}
+
+// SyntaxNode::children の戻り値。子の数は高々 6 なので固定長の配列に詰めて返す
+pub type Children<'a> = std::iter::Flatten<std::array::IntoIter<Option<SyntaxElement<'a>>, 6>>;
+
+pub struct Tokens<'a> {
+    // 未訪問の要素。末尾から順に訪れる
+    stack: Vec<SyntaxElement<'a>>,
+    pos: Position,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = (&'a SyntaxToken, Range);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.pop()? {
+                SyntaxElement::Token(token) => {
+                    let start = self.pos;
+                    self.pos.advance(token.full_width());
+                    return Some((
+                        token,
+                        Range {
+                            start,
+                            end: self.pos,
+                        },
+                    ));
+                }
+                SyntaxElement::Node(node) => self.stack.extend(node.children().rev()),
+            }
         }
     }
 }
 
-#[cfg(test)]
-fn string_from_token(
-    source: &Source,
-    mut pos: Position,
-    token: &SyntaxToken,
-) -> (Position, String) {
-    let token_start_pos = pos;
-    pos.advance(token.full_width());
-    let token_end_pos = pos;
-    let token_range = Range {
-        start: token_start_pos,
-        end: token_end_pos,
-    };
-    (pos, source.get(&token_range).iter().collect())
+pub struct Descendants<'a> {
+    // 未訪問のノードとその開始位置。末尾から順に訪れる
+    stack: Vec<(&'a SyntaxNode, Position)>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = (&'a SyntaxNode, Range);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, start) = self.stack.pop()?;
+        // 子ノードの開始位置は直前までの子の幅から求まる。子ノードは幅を持っているので部分木は辿らない
+        let end = start + node.full_width();
+        let mut pos = end;
+        for child in node.children().rev() {
+            pos.backward(child.full_width());
+            if let SyntaxElement::Node(child) = child {
+                self.stack.push((child, pos));
+            }
+        }
+        Some((node, Range { start, end }))
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -149,6 +705,10 @@ pub enum TokenKind {
     Number,
     Ident,
     Let,
+    If,
+    Then,
+    Else,
+    Fun,
     OpenParen,
     CloseParen,
     Equal,
@@ -157,13 +717,110 @@ pub enum TokenKind {
     Minus,
     Ast,
     Slash,
+    True,
+    False,
+    EqualEqual,
+    BangEqual,
+    Lt,
+    LtEqual,
+    Gt,
+    GtEqual,
+    AmpAmp,
+    PipePipe,
+    Bang,
+    Arrow,
     Error,
 }
 
-pub const ALL_TOKEN_KINDS: [TokenKind; 12] = [
+impl TokenKind {
+    // 綴りが決まっているトークンの綴り
+    pub fn text(&self) -> Option<&'static str> {
+        match self {
+            TokenKind::Let => Some("let"),
+            TokenKind::If => Some("if"),
+            TokenKind::Then => Some("then"),
+            TokenKind::Else => Some("else"),
+            TokenKind::Fun => Some("fun"),
+            TokenKind::Arrow => Some("->"),
+            TokenKind::OpenParen => Some("("),
+            TokenKind::CloseParen => Some(")"),
+            TokenKind::Equal => Some("="),
+            TokenKind::Semicolon => Some(";"),
+            TokenKind::Plus => Some("+"),
+            TokenKind::Minus => Some("-"),
+            TokenKind::Ast => Some("*"),
+            TokenKind::Slash => Some("/"),
+            TokenKind::True => Some("true"),
+            TokenKind::False => Some("false"),
+            TokenKind::EqualEqual => Some("=="),
+            TokenKind::BangEqual => Some("!="),
+            TokenKind::Lt => Some("<"),
+            TokenKind::LtEqual => Some("<="),
+            TokenKind::Gt => Some(">"),
+            TokenKind::GtEqual => Some(">="),
+            TokenKind::AmpAmp => Some("&&"),
+            TokenKind::PipePipe => Some("||"),
+            TokenKind::Bang => Some("!"),
+            TokenKind::Number | TokenKind::Ident | TokenKind::Error => None,
+        }
+    }
+
+    // 構文木や診断情報を書き出す形式 (JSON など) でのトークンの種類の名前
+    // 書き出す形式を保つため、Debug の表示ではなくここで決めた名前を使う
+    pub fn name(&self) -> &'static str {
+        match self {
+            TokenKind::Number => "Number",
+            TokenKind::Ident => "Ident",
+            TokenKind::Let => "Let",
+            TokenKind::If => "If",
+            TokenKind::Then => "Then",
+            TokenKind::Else => "Else",
+            TokenKind::Fun => "Fun",
+            TokenKind::OpenParen => "OpenParen",
+            TokenKind::CloseParen => "CloseParen",
+            TokenKind::Equal => "Equal",
+            TokenKind::Semicolon => "Semicolon",
+            TokenKind::Plus => "Plus",
+            TokenKind::Minus => "Minus",
+            TokenKind::Ast => "Ast",
+            TokenKind::Slash => "Slash",
+            TokenKind::True => "True",
+            TokenKind::False => "False",
+            TokenKind::EqualEqual => "EqualEqual",
+            TokenKind::BangEqual => "BangEqual",
+            TokenKind::Lt => "Lt",
+            TokenKind::LtEqual => "LtEqual",
+            TokenKind::Gt => "Gt",
+            TokenKind::GtEqual => "GtEqual",
+            TokenKind::AmpAmp => "AmpAmp",
+            TokenKind::PipePipe => "PipePipe",
+            TokenKind::Bang => "Bang",
+            TokenKind::Arrow => "Arrow",
+            TokenKind::Error => "Error",
+        }
+    }
+}
+
+// 診断メッセージ向けの表示 (e.g. 'let', identifier)
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Number => write!(f, "number"),
+            TokenKind::Ident => write!(f, "identifier"),
+            TokenKind::Error => write!(f, "unknown token"),
+            _ => write!(f, "'{}'", self.text().unwrap()),
+        }
+    }
+}
+
+pub const ALL_TOKEN_KINDS: [TokenKind; 28] = [
     TokenKind::Number,
     TokenKind::Ident,
     TokenKind::Let,
+    TokenKind::If,
+    TokenKind::Then,
+    TokenKind::Else,
+    TokenKind::Fun,
     TokenKind::OpenParen,
     TokenKind::CloseParen,
     TokenKind::Equal,
@@ -172,10 +829,65 @@ pub const ALL_TOKEN_KINDS: [TokenKind; 12] = [
     TokenKind::Minus,
     TokenKind::Ast,
     TokenKind::Slash,
+    TokenKind::True,
+    TokenKind::False,
+    TokenKind::EqualEqual,
+    TokenKind::BangEqual,
+    TokenKind::Lt,
+    TokenKind::LtEqual,
+    TokenKind::Gt,
+    TokenKind::GtEqual,
+    TokenKind::AmpAmp,
+    TokenKind::PipePipe,
+    TokenKind::Bang,
+    TokenKind::Arrow,
     TokenKind::Error,
 ];
 
-#[derive(Debug, PartialEq, Eq)]
+// 一次式を始められるトークン
+pub const PRIMARY_EXPR_BEGIN_TOKEN_KINDS: [TokenKind; 5] = [
+    TokenKind::Ident,
+    TokenKind::Number,
+    TokenKind::True,
+    TokenKind::False,
+    TokenKind::OpenParen,
+];
+
+pub const UNARY_OPERATOR_TOKEN_KINDS: [TokenKind; 3] =
+    [TokenKind::Minus, TokenKind::Plus, TokenKind::Bang];
+
+// 優先順位と結合性は構文解析器が決める
+pub const BINARY_OPERATOR_TOKEN_KINDS: [TokenKind; 12] = [
+    TokenKind::Plus,
+    TokenKind::Minus,
+    TokenKind::Ast,
+    TokenKind::Slash,
+    TokenKind::EqualEqual,
+    TokenKind::BangEqual,
+    TokenKind::Lt,
+    TokenKind::LtEqual,
+    TokenKind::Gt,
+    TokenKind::GtEqual,
+    TokenKind::AmpAmp,
+    TokenKind::PipePipe,
+];
+
+// 式を始められるトークン。一次式を始めるトークン、式を始める予約語、前置演算子からなる
+pub const EXPR_BEGIN_TOKEN_KINDS: [TokenKind; 11] = [
+    TokenKind::Ident,
+    TokenKind::Number,
+    TokenKind::True,
+    TokenKind::False,
+    TokenKind::Let,
+    TokenKind::If,
+    TokenKind::Fun,
+    TokenKind::OpenParen,
+    TokenKind::Minus,
+    TokenKind::Plus,
+    TokenKind::Bang,
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SyntaxToken {
     pub kind: TokenKind,
     pub leading_trivia_width: usize,
@@ -197,3 +909,146 @@ impl SyntaxToken {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        SyntaxNode, TokenKind, EXPR_BEGIN_TOKEN_KINDS, PRIMARY_EXPR_BEGIN_TOKEN_KINDS,
+        UNARY_OPERATOR_TOKEN_KINDS,
+    };
+    use crate::parse::parse;
+    use crate::source::{Position, Range, Source};
+
+    fn range(start: usize, end: usize) -> Range {
+        Range {
+            start: Position(start),
+            end: Position(end),
+        }
+    }
+
+    #[test]
+    fn test_text() {
+        let src = "let a = /* one */ 1; // comment\n(a @ + 2)";
+        let source = Source::from_str(src);
+        let (node, _) = parse(&source);
+        assert_eq!(node.text(&source), format!("{src}\n"));
+    }
+
+    #[test]
+    fn test_text_at() {
+        let source = Source::from_str("1 * (a /* c */ + 2) ");
+        let (node, _) = parse(&source);
+        let SyntaxNode::BinOp { rhs_expr, .. } = &node else {
+            panic!("{node:?}");
+        };
+        // 部分木の位置は descendants で得られる
+        let (_, rhs_range) = node
+            .descendants()
+            .find(|(descendant, _)| std::ptr::eq(*descendant, &**rhs_expr))
+            .unwrap();
+        assert_eq!(
+            rhs_expr.text_at(&source, rhs_range.start),
+            "(a /* c */ + 2) \n"
+        );
+        let texts: Vec<_> = node
+            .descendants()
+            .map(|(descendant, range)| descendant.text_at(&source, range.start))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "1 * (a /* c */ + 2) \n",
+                "1 ",
+                "(a /* c */ + 2) \n",
+                "a /* c */ + 2",
+                "a /* c */ ",
+                "2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expr_begin_token_kinds() {
+        let mut expected: Vec<_> = PRIMARY_EXPR_BEGIN_TOKEN_KINDS
+            .into_iter()
+            .chain([TokenKind::Let, TokenKind::If, TokenKind::Fun])
+            .chain(UNARY_OPERATOR_TOKEN_KINDS)
+            .map(|kind| kind.name())
+            .collect();
+        let mut actual: Vec<_> = EXPR_BEGIN_TOKEN_KINDS
+            .iter()
+            .map(|kind| kind.name())
+            .collect();
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_tokens() {
+        let source = Source::from_str("(a + 1) ");
+        let (node, _) = parse(&source);
+        let tokens: Vec<_> = node
+            .tokens()
+            .map(|(token, range)| (token.kind.clone(), range))
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::OpenParen, range(0, 1)),
+                (TokenKind::Ident, range(1, 3)),
+                (TokenKind::Plus, range(3, 5)),
+                (TokenKind::Number, range(5, 6)),
+                (TokenKind::CloseParen, range(6, 9)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_descendants() {
+        let source = Source::from_str("1 * (a)");
+        let (node, _) = parse(&source);
+        let descendants: Vec<_> = node.descendants().map(|(_, range)| range).collect();
+        assert_eq!(
+            descendants,
+            vec![range(0, 8), range(0, 2), range(4, 8), range(5, 6)]
+        );
+        assert!(matches!(
+            node.descendants().last(),
+            Some((SyntaxNode::Var { .. }, _))
+        ));
+    }
+
+    #[test]
+    fn test_descendants_deep() {
+        // 左に深い木でも、各ノードの位置は子の幅から求まる
+        let n = 2000;
+        let source = Source::from_str(&format!("{}1", "1+".repeat(n)));
+        let (node, _) = parse(&source);
+        let descendants: Vec<_> = node.descendants().map(|(_, range)| range).collect();
+        assert_eq!(descendants.len(), 2 * n + 1);
+        assert_eq!(descendants[0], range(0, 2 * n + 2));
+        assert_eq!(descendants[n], range(0, 1));
+        assert_eq!(descendants[2 * n], range(2 * n, 2 * n + 2));
+    }
+
+    #[test]
+    fn test_full_width_after_extend_trivia() {
+        let source = Source::from_str("f(1 + 2)");
+        let (mut node, _) = parse(&source);
+        node.extend_leading_trivia_width(2);
+        node.extend_trailing_trivia_width(3);
+        assert_eq!(node.full_width(), 9 + 5);
+        let descendants: Vec<_> = node.descendants().map(|(_, range)| range).collect();
+        assert_eq!(
+            descendants,
+            vec![
+                range(0, 14),
+                range(0, 3),
+                range(4, 9),
+                range(4, 6),
+                range(8, 9)
+            ]
+        );
+    }
+}
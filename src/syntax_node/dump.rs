@@ -0,0 +1,352 @@
+use super::green::NodeKind;
+use super::{SyntaxElement, SyntaxNode, SyntaxToken};
+use crate::source::{Position, Range, Source};
+use serde_json::{json, Map, Value};
+
+// 構文木を他のツールから読める形式で書き出す
+// 位置と綴りは元のファイルのものを使う。"\r\n" はそのまま書き出し、Source が末尾に補った改行は含めないので、
+// トークンの leading_trivia, text, trailing_trivia を順に繋げると元のファイルに戻る
+// 位置はファイル先頭からの文字数で表す
+// ノードの range は trivia を含み、トークンの range は trivia を含まない
+
+// JSON の形式を変えたら上げる
+pub const JSON_SCHEMA_VERSION: u64 = 2;
+
+// e.g.
+// {"version":2,"file":"main.denvl","tree":
+//   {"type":"node","kind":"Var","range":{"start":0,"end":2},"children":[
+//     {"type":"token","kind":"Ident","range":{"start":0,"end":1},"text":"a",
+//      "leading_trivia":"","trailing_trivia":"\n"}]}}
+pub fn to_json(filename: &str, source: &Source, node: &SyntaxNode) -> Value {
+    json!({
+        "version": JSON_SCHEMA_VERSION,
+        "file": filename,
+        "tree": node_to_json(source, node, Position::start()).0,
+    })
+}
+
+// ノードとその終了位置を返す
+// json! に子の Value を渡すと複製されて深い木で遅くなるので、Map を直接組み立てる
+fn node_to_json(source: &Source, node: &SyntaxNode, offset: Position) -> (Value, Position) {
+    let mut pos = offset;
+    let mut children = vec![];
+    for child in node.children() {
+        let value;
+        (value, pos) = match child {
+            SyntaxElement::Node(node) => node_to_json(source, node, pos),
+            SyntaxElement::Token(token) => {
+                (token_to_json(source, token, pos), pos + token.full_width())
+            }
+        };
+        children.push(value);
+    }
+    let mut object = Map::new();
+    object.insert("type".to_string(), json!("node"));
+    object.insert("kind".to_string(), json!(NodeKind::of(node).name()));
+    object.insert(
+        "range".to_string(),
+        range_to_json(original_range(
+            source,
+            Range {
+                start: offset,
+                end: pos,
+            },
+        )),
+    );
+    object.insert("children".to_string(), Value::Array(children));
+    (Value::Object(object), pos)
+}
+
+fn token_to_json(source: &Source, token: &SyntaxToken, offset: Position) -> Value {
+    let TokenParts {
+        leading_trivia,
+        text_range,
+        text,
+        trailing_trivia,
+    } = token_parts(source, token, offset);
+    json!({
+        "type": "token",
+        "kind": token.kind.name(),
+        "range": range_to_json(text_range),
+        "text": text,
+        "leading_trivia": leading_trivia,
+        "trailing_trivia": trailing_trivia,
+    })
+}
+
+fn range_to_json(range: Range) -> Value {
+    json!({ "start": range.start.0, "end": range.end.0 })
+}
+
+// e.g.
+// (BinOp 0..6
+//   (Int 0..2
+//     (token Number 0..1 "1" (trailing " ")))
+//   (token Plus 2..3 "+" (trailing " "))
+//   (Int 4..6
+//     (token Number 4..5 "2" (trailing "\n"))))
+pub fn to_sexp(source: &Source, node: &SyntaxNode) -> String {
+    let mut lines = vec![];
+    node_to_sexp(source, node, Position::start(), 0, &mut lines);
+    lines.join("\n")
+}
+
+fn node_to_sexp(
+    source: &Source,
+    node: &SyntaxNode,
+    offset: Position,
+    depth: usize,
+    lines: &mut Vec<String>,
+) -> Position {
+    let index = lines.len();
+    lines.push(String::new());
+    let mut pos = offset;
+    for child in node.children() {
+        pos = match child {
+            SyntaxElement::Node(node) => node_to_sexp(source, node, pos, depth + 1, lines),
+            SyntaxElement::Token(token) => {
+                lines.push(token_to_sexp(source, token, pos, depth + 1));
+                pos + token.full_width()
+            }
+        };
+    }
+    lines[index] = format!(
+        "{}({} {}..{}",
+        indent(depth),
+        NodeKind::of(node).name(),
+        source.original_offset(offset),
+        source.original_offset(pos)
+    );
+    lines.last_mut().unwrap().push(')');
+    pos
+}
+
+fn token_to_sexp(source: &Source, token: &SyntaxToken, offset: Position, depth: usize) -> String {
+    let TokenParts {
+        leading_trivia,
+        text_range,
+        text,
+        trailing_trivia,
+    } = token_parts(source, token, offset);
+    let mut result = format!(
+        "{}(token {} {}..{} {:?}",
+        indent(depth),
+        token.kind.name(),
+        text_range.start.0,
+        text_range.end.0,
+        text
+    );
+    if !leading_trivia.is_empty() {
+        result += &format!(" (leading {leading_trivia:?})");
+    }
+    if !trailing_trivia.is_empty() {
+        result += &format!(" (trailing {trailing_trivia:?})");
+    }
+    result.push(')');
+    result
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+struct TokenParts {
+    leading_trivia: String,
+    text_range: Range,
+    text: String,
+    trailing_trivia: String,
+}
+
+fn token_parts(source: &Source, token: &SyntaxToken, offset: Position) -> TokenParts {
+    let text_start = offset + token.leading_trivia_width;
+    let text_end = text_start + token.token_width;
+    let get = |start: Position, end: Position| source.original_text(&Range { start, end });
+    TokenParts {
+        leading_trivia: get(offset, text_start),
+        text_range: original_range(
+            source,
+            Range {
+                start: text_start,
+                end: text_end,
+            },
+        ),
+        text: get(text_start, text_end),
+        trailing_trivia: get(text_end, text_end + token.trailing_trivia_width),
+    }
+}
+
+fn original_range(source: &Source, range: Range) -> Range {
+    Range {
+        start: Position(source.original_offset(range.start)),
+        end: Position(source.original_offset(range.end)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{to_json, to_sexp};
+    use crate::parse::parse;
+    use crate::source::Source;
+    use crate::syntax_node::green::NodeKind;
+    use crate::syntax_node::ALL_TOKEN_KINDS;
+    use serde_json::json;
+
+    #[test]
+    fn test_sexp() {
+        let source = Source::from_str("1 + 2\n");
+        let (node, _) = parse(&source);
+        assert_eq!(
+            to_sexp(&source, &node),
+            r#"(BinOp 0..6
+  (Int 0..2
+    (token Number 0..1 "1" (trailing " ")))
+  (token Plus 2..3 "+" (trailing " "))
+  (Int 4..6
+    (token Number 4..5 "2" (trailing "\n"))))"#
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let source = Source::from_str("/* c */ a\n");
+        let (node, _) = parse(&source);
+        assert_eq!(
+            to_json("main.denvl", &source, &node),
+            json!({
+                "version": 2,
+                "file": "main.denvl",
+                "tree": {
+                    "type": "node",
+                    "kind": "Var",
+                    "range": { "start": 0, "end": 10 },
+                    "children": [{
+                        "type": "token",
+                        "kind": "Ident",
+                        "range": { "start": 8, "end": 9 },
+                        "text": "a",
+                        "leading_trivia": "/* c */ ",
+                        "trailing_trivia": "\n",
+                    }],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_deep() {
+        let n = 500;
+        let source = Source::from_str(&format!("{}1\n", "1+".repeat(n)));
+        let (node, _) = parse(&source);
+        let json = to_json("main.denvl", &source, &node);
+        let mut tree = &json["tree"];
+        assert_eq!(tree["range"], json!({ "start": 0, "end": 2 * n + 2 }));
+        for _ in 0..n {
+            tree = &tree["children"][0];
+        }
+        assert_eq!(tree["kind"], "Int");
+        assert_eq!(tree["range"], json!({ "start": 0, "end": 1 }));
+    }
+
+    #[test]
+    fn test_missing_token() {
+        let source = Source::from_str("(a\n");
+        let (node, _) = parse(&source);
+        let sexp = to_sexp(&source, &node);
+        assert!(sexp.ends_with(r#"(token CloseParen 3..3 ""))"#));
+    }
+
+    // トークンの trivia と綴りを順に繋げると元のファイルに戻る
+    #[test]
+    fn test_lossless() {
+        for original in ["let a = 1;\r\n(a\r\n", "1 +\r\n\r\n2", "(a", "a // c\n"] {
+            let source = Source::from_str(original);
+            let (node, _) = parse(&source);
+            let json = to_json("main.denvl", &source, &node);
+            let mut text = String::new();
+            let mut stack = vec![&json["tree"]];
+            while let Some(value) = stack.pop() {
+                if value["type"] == "token" {
+                    for key in ["leading_trivia", "text", "trailing_trivia"] {
+                        text += value[key].as_str().unwrap();
+                    }
+                } else {
+                    stack.extend(value["children"].as_array().unwrap().iter().rev());
+                }
+            }
+            assert_eq!(text, original);
+            let len = original.chars().count();
+            assert_eq!(json["tree"]["range"], json!({ "start": 0, "end": len }));
+        }
+    }
+
+    #[test]
+    fn test_crlf() {
+        let source = Source::from_str("a\r\n+ b\r\n");
+        let (node, _) = parse(&source);
+        assert_eq!(
+            to_sexp(&source, &node),
+            r#"(BinOp 0..8
+  (Var 0..3
+    (token Ident 0..1 "a" (trailing "\r\n")))
+  (token Plus 3..4 "+" (trailing " "))
+  (Var 5..8
+    (token Ident 5..6 "b" (trailing "\r\n"))))"#
+        );
+    }
+
+    // 書き出す形式の一部なので、名前を変えるときは JSON_SCHEMA_VERSION を上げる
+    #[test]
+    fn test_kind_names() {
+        let token_kind_names: Vec<_> = ALL_TOKEN_KINDS.iter().map(|kind| kind.name()).collect();
+        assert_eq!(
+            token_kind_names,
+            vec![
+                "Number",
+                "Ident",
+                "Let",
+                "If",
+                "Then",
+                "Else",
+                "Fun",
+                "OpenParen",
+                "CloseParen",
+                "Equal",
+                "Semicolon",
+                "Plus",
+                "Minus",
+                "Ast",
+                "Slash",
+                "True",
+                "False",
+                "EqualEqual",
+                "BangEqual",
+                "Lt",
+                "LtEqual",
+                "Gt",
+                "GtEqual",
+                "AmpAmp",
+                "PipePipe",
+                "Bang",
+                "Arrow",
+                "Error",
+            ]
+        );
+        let node_kinds = [
+            (NodeKind::Int, "Int"),
+            (NodeKind::Var, "Var"),
+            (NodeKind::Bool, "Bool"),
+            (NodeKind::Let, "Let"),
+            (NodeKind::If, "If"),
+            (NodeKind::Lambda, "Lambda"),
+            (NodeKind::Call, "Call"),
+            (NodeKind::BinOp, "BinOp"),
+            (NodeKind::Unary, "Unary"),
+            (NodeKind::Paren, "Paren"),
+            (NodeKind::Error, "Error"),
+            (NodeKind::Missing, "Missing"),
+        ];
+        for (kind, name) in node_kinds {
+            assert_eq!(kind.name(), name);
+        }
+    }
+}
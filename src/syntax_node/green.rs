@@ -0,0 +1,119 @@
+use super::{SyntaxElement, SyntaxNode, SyntaxToken};
+use std::rc::Rc;
+
+// 編集をまたいで共有できる不変な構文木 (green tree)
+// 各要素は自身の幅だけを持ち、親や絶対位置は持たない。それらは red::RedNode を介して辿る
+// 部分木は Rc で共有するので、編集で変わらなかった部分木は新しい木でもそのまま使える
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Int,
+    Var,
+    Bool,
+    Let,
+    If,
+    Lambda,
+    Call,
+    BinOp,
+    Unary,
+    Paren,
+    Error,
+    Missing,
+}
+
+impl NodeKind {
+    pub fn of(node: &SyntaxNode) -> Self {
+        match node {
+            SyntaxNode::Int { .. } => NodeKind::Int,
+            SyntaxNode::Var { .. } => NodeKind::Var,
+            SyntaxNode::Bool { .. } => NodeKind::Bool,
+            SyntaxNode::Let { .. } => NodeKind::Let,
+            SyntaxNode::If { .. } => NodeKind::If,
+            SyntaxNode::Lambda { .. } => NodeKind::Lambda,
+            SyntaxNode::Call { .. } => NodeKind::Call,
+            SyntaxNode::BinOp { .. } => NodeKind::BinOp,
+            SyntaxNode::Unary { .. } => NodeKind::Unary,
+            SyntaxNode::Paren { .. } => NodeKind::Paren,
+            SyntaxNode::Error { .. } => NodeKind::Error,
+            SyntaxNode::Missing { .. } => NodeKind::Missing,
+        }
+    }
+
+    // 構文木を書き出す形式 (JSON など) でのノードの種類の名前
+    // 書き出す形式を保つため、Debug の表示ではなくここで決めた名前を使う
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeKind::Int => "Int",
+            NodeKind::Var => "Var",
+            NodeKind::Bool => "Bool",
+            NodeKind::Let => "Let",
+            NodeKind::If => "If",
+            NodeKind::Lambda => "Lambda",
+            NodeKind::Call => "Call",
+            NodeKind::BinOp => "BinOp",
+            NodeKind::Unary => "Unary",
+            NodeKind::Paren => "Paren",
+            NodeKind::Error => "Error",
+            NodeKind::Missing => "Missing",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GreenNode {
+    kind: NodeKind,
+    // trivia を含む幅
+    width: usize,
+    children: Vec<GreenElement>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<SyntaxToken>),
+}
+
+impl GreenElement {
+    pub fn width(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.width(),
+            GreenElement::Token(token) => token.full_width(),
+        }
+    }
+}
+
+impl GreenNode {
+    pub fn new(kind: NodeKind, children: Vec<GreenElement>) -> Rc<Self> {
+        let width = children.iter().map(GreenElement::width).sum();
+        Rc::new(GreenNode {
+            kind,
+            width,
+            children,
+        })
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+
+    // 子の並びは SyntaxNode::children と同じくソースコード上の出現順
+    pub fn from_syntax_node(node: &SyntaxNode) -> Rc<Self> {
+        let kind = NodeKind::of(node);
+        let children = node
+            .children()
+            .map(|child| match child {
+                SyntaxElement::Node(node) => GreenElement::Node(GreenNode::from_syntax_node(node)),
+                SyntaxElement::Token(token) => GreenElement::Token(Rc::new(token.clone())),
+            })
+            .collect();
+        GreenNode::new(kind, children)
+    }
+}
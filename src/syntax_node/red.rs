@@ -0,0 +1,261 @@
+use super::green::{GreenElement, GreenNode, NodeKind};
+use super::SyntaxToken;
+use crate::source::{Position, Range};
+use std::rc::Rc;
+
+// green tree の上を辿るためのカーソル (red tree)
+// 親へのポインタと絶対位置を持つ。辿るときに必要な分だけ作るので、green tree は共有したまま使える
+
+#[derive(Clone, Debug)]
+pub struct RedNode(Rc<RedNodeData>);
+
+#[derive(Debug)]
+struct RedNodeData {
+    green: Rc<GreenNode>,
+    parent: Option<RedNode>,
+    // 親の子の中での位置
+    index: usize,
+    // trivia を含めた先頭の絶対位置
+    offset: Position,
+}
+
+#[derive(Clone, Debug)]
+pub struct RedToken {
+    green: Rc<SyntaxToken>,
+    parent: RedNode,
+    index: usize,
+    offset: Position,
+}
+
+#[derive(Clone, Debug)]
+pub enum RedElement {
+    Node(RedNode),
+    Token(RedToken),
+}
+
+impl RedNode {
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        RedNode(Rc::new(RedNodeData {
+            green,
+            parent: None,
+            index: 0,
+            offset: Position::start(),
+        }))
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.0.green.kind()
+    }
+
+    pub fn parent(&self) -> Option<RedNode> {
+        self.0.parent.clone()
+    }
+
+    // trivia を含めた範囲
+    pub fn range(&self) -> Range {
+        Range {
+            start: self.0.offset,
+            end: self.0.offset + self.0.green.width(),
+        }
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = RedElement> + '_ {
+        let mut offset = self.0.offset;
+        self.0
+            .green
+            .children()
+            .iter()
+            .enumerate()
+            .map(move |(index, green)| {
+                let child = self.make_child(index, green, offset);
+                offset.advance(green.width());
+                child
+            })
+    }
+
+    pub fn child_nodes(&self) -> impl Iterator<Item = RedNode> + '_ {
+        self.children().filter_map(|child| match child {
+            RedElement::Node(node) => Some(node),
+            RedElement::Token(_) => None,
+        })
+    }
+
+    pub fn child(&self, index: usize) -> Option<RedElement> {
+        self.children().nth(index)
+    }
+
+    pub fn next_sibling(&self) -> Option<RedElement> {
+        self.parent()?.child(self.0.index + 1)
+    }
+
+    pub fn prev_sibling(&self) -> Option<RedElement> {
+        self.parent()?.child(self.0.index.checked_sub(1)?)
+    }
+
+    // pos を含む最も深いノード
+    pub fn node_at_offset(&self, pos: Position) -> Option<RedNode> {
+        if !contains(self.range(), pos) {
+            return None;
+        }
+        Some(
+            self.child_nodes()
+                .find_map(|child| child.node_at_offset(pos))
+                .unwrap_or_else(|| self.clone()),
+        )
+    }
+
+    // pos を含むトークン。trivia の上の位置なら、その trivia を持つトークンを返す
+    pub fn token_at_offset(&self, pos: Position) -> Option<RedToken> {
+        if !contains(self.range(), pos) {
+            return None;
+        }
+        self.children().find_map(|child| match child {
+            RedElement::Node(node) => node.token_at_offset(pos),
+            RedElement::Token(token) if contains(token.range(), pos) => Some(token),
+            RedElement::Token(_) => None,
+        })
+    }
+
+    fn make_child(&self, index: usize, green: &GreenElement, offset: Position) -> RedElement {
+        match green {
+            GreenElement::Node(green) => RedElement::Node(RedNode(Rc::new(RedNodeData {
+                green: green.clone(),
+                parent: Some(self.clone()),
+                index,
+                offset,
+            }))),
+            GreenElement::Token(green) => RedElement::Token(RedToken {
+                green: green.clone(),
+                parent: self.clone(),
+                index,
+                offset,
+            }),
+        }
+    }
+}
+
+impl RedToken {
+    pub fn green(&self) -> &SyntaxToken {
+        &self.green
+    }
+
+    pub fn parent(&self) -> RedNode {
+        self.parent.clone()
+    }
+
+    // trivia を含めた範囲
+    pub fn range(&self) -> Range {
+        Range {
+            start: self.offset,
+            end: self.offset + self.green.full_width(),
+        }
+    }
+
+    // trivia を除いたトークンそのものの範囲
+    pub fn text_range(&self) -> Range {
+        let start = self.offset + self.green.leading_trivia_width;
+        Range {
+            start,
+            end: start + self.green.token_width,
+        }
+    }
+
+    pub fn next_sibling(&self) -> Option<RedElement> {
+        self.parent.child(self.index + 1)
+    }
+
+    pub fn prev_sibling(&self) -> Option<RedElement> {
+        self.parent.child(self.index.checked_sub(1)?)
+    }
+}
+
+impl RedElement {
+    pub fn range(&self) -> Range {
+        match self {
+            RedElement::Node(node) => node.range(),
+            RedElement::Token(token) => token.range(),
+        }
+    }
+}
+
+fn contains(range: Range, pos: Position) -> bool {
+    range.start <= pos && pos < range.end
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RedElement, RedNode};
+    use crate::parse::parse;
+    use crate::source::{Position, Range, Source};
+    use crate::syntax_node::green::{GreenNode, NodeKind};
+    use crate::syntax_node::TokenKind;
+
+    fn root(src: &str) -> (Source, RedNode) {
+        let source = Source::from_str(src);
+        let (node, _) = parse(&source);
+        let root = RedNode::new_root(GreenNode::from_syntax_node(&node));
+        (source, root)
+    }
+
+    fn range(start: usize, end: usize) -> Range {
+        Range {
+            start: Position(start),
+            end: Position(end),
+        }
+    }
+
+    #[test]
+    fn test_offsets() {
+        let (source, root) = root("let a = 1 + b; a");
+        assert_eq!(root.kind(), NodeKind::Let);
+        assert_eq!(root.range(), source.range());
+
+        let init = root.child_nodes().next().unwrap();
+        assert_eq!(init.kind(), NodeKind::BinOp);
+        assert_eq!(init.range(), range(8, 13));
+        assert_eq!(init.parent().unwrap().kind(), NodeKind::Let);
+
+        let token = root.token_at_offset(Position(12)).unwrap();
+        assert_eq!(token.green().kind, TokenKind::Ident);
+        assert_eq!(token.text_range(), range(12, 13));
+        assert_eq!(token.parent().kind(), NodeKind::Var);
+    }
+
+    #[test]
+    fn test_siblings() {
+        let (_, root) = root("1 + 2");
+        let lhs = root.child_nodes().next().unwrap();
+        let Some(RedElement::Token(plus)) = lhs.next_sibling() else {
+            panic!("'+' follows lhs");
+        };
+        assert_eq!(plus.green().kind, TokenKind::Plus);
+        let Some(RedElement::Node(rhs)) = plus.next_sibling() else {
+            panic!("rhs follows '+'");
+        };
+        assert_eq!(rhs.range(), range(4, 6));
+        assert!(rhs.next_sibling().is_none());
+        assert!(lhs.prev_sibling().is_none());
+        assert_eq!(rhs.prev_sibling().unwrap().range(), range(2, 4));
+    }
+
+    #[test]
+    fn test_at_offset() {
+        let (_, root) = root("(a) // comment");
+        assert_eq!(
+            root.node_at_offset(Position(1)).unwrap().kind(),
+            NodeKind::Var
+        );
+        assert_eq!(
+            root.node_at_offset(Position(0)).unwrap().kind(),
+            NodeKind::Paren
+        );
+        // trivia の上はその trivia を持つトークン
+        let token = root.token_at_offset(Position(6)).unwrap();
+        assert_eq!(token.green().kind, TokenKind::CloseParen);
+        assert!(root.token_at_offset(Position(100)).is_none());
+    }
+}
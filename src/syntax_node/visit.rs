@@ -0,0 +1,633 @@
+use super::{SyntaxNode, SyntaxToken};
+
+// 構文木を辿るための trait
+// 各 visit_* の既定の実装は対応する walk_* を呼び、子をソースコード上の出現順に訪れる
+// 必要なメソッドだけを上書きし、子も辿りたいときは上書きしたメソッドから walk_* を呼ぶ
+
+pub trait Visitor {
+    fn visit_node(&mut self, node: &SyntaxNode) {
+        walk_node(self, node)
+    }
+
+    fn visit_int(&mut self, token: &SyntaxToken) {
+        self.visit_token(token)
+    }
+
+    fn visit_var(&mut self, token: &SyntaxToken) {
+        self.visit_token(token)
+    }
+
+    fn visit_bool(&mut self, token: &SyntaxToken) {
+        self.visit_token(token)
+    }
+
+    fn visit_let(
+        &mut self,
+        let_token: &SyntaxToken,
+        ident_token: &SyntaxToken,
+        equal_token: &SyntaxToken,
+        init_expr: &SyntaxNode,
+        semicolon_token: &SyntaxToken,
+        body_expr: &SyntaxNode,
+    ) {
+        walk_let(
+            self,
+            let_token,
+            ident_token,
+            equal_token,
+            init_expr,
+            semicolon_token,
+            body_expr,
+        )
+    }
+
+    fn visit_if(
+        &mut self,
+        if_token: &SyntaxToken,
+        cond_expr: &SyntaxNode,
+        then_token: &SyntaxToken,
+        then_expr: &SyntaxNode,
+        else_token: &SyntaxToken,
+        else_expr: &SyntaxNode,
+    ) {
+        walk_if(
+            self, if_token, cond_expr, then_token, then_expr, else_token, else_expr,
+        )
+    }
+
+    fn visit_lambda(
+        &mut self,
+        fun_token: &SyntaxToken,
+        param_token: &SyntaxToken,
+        arrow_token: &SyntaxToken,
+        body_expr: &SyntaxNode,
+    ) {
+        walk_lambda(self, fun_token, param_token, arrow_token, body_expr)
+    }
+
+    fn visit_call(
+        &mut self,
+        callee_expr: &SyntaxNode,
+        open_paren_token: &SyntaxToken,
+        arg_expr: &SyntaxNode,
+        close_paren_token: &SyntaxToken,
+    ) {
+        walk_call(
+            self,
+            callee_expr,
+            open_paren_token,
+            arg_expr,
+            close_paren_token,
+        )
+    }
+
+    fn visit_binop(
+        &mut self,
+        lhs_expr: &SyntaxNode,
+        binop_token: &SyntaxToken,
+        rhs_expr: &SyntaxNode,
+    ) {
+        walk_binop(self, lhs_expr, binop_token, rhs_expr)
+    }
+
+    fn visit_unary(&mut self, unop_token: &SyntaxToken, operand_expr: &SyntaxNode) {
+        walk_unary(self, unop_token, operand_expr)
+    }
+
+    fn visit_paren(
+        &mut self,
+        open_paren_token: &SyntaxToken,
+        inner_expr: &SyntaxNode,
+        close_paren_token: &SyntaxToken,
+    ) {
+        walk_paren(self, open_paren_token, inner_expr, close_paren_token)
+    }
+
+    fn visit_error(&mut self, token: &SyntaxToken) {
+        self.visit_token(token)
+    }
+
+    fn visit_missing(&mut self, token: &SyntaxToken) {
+        self.visit_token(token)
+    }
+
+    fn visit_token(&mut self, _token: &SyntaxToken) {}
+}
+
+pub fn walk_node<V: Visitor + ?Sized>(visitor: &mut V, node: &SyntaxNode) {
+    use SyntaxNode::*;
+    match node {
+        Int { token } => visitor.visit_int(token),
+        Var { token } => visitor.visit_var(token),
+        Bool { token } => visitor.visit_bool(token),
+        Let {
+            let_token,
+            ident_token,
+            equal_token,
+            init_expr,
+            semicolon_token,
+            body_expr,
+        } => visitor.visit_let(
+            let_token,
+            ident_token,
+            equal_token,
+            init_expr,
+            semicolon_token,
+            body_expr,
+        ),
+        If {
+            if_token,
+            cond_expr,
+            then_token,
+            then_expr,
+            else_token,
+            else_expr,
+        } => visitor.visit_if(
+            if_token, cond_expr, then_token, then_expr, else_token, else_expr,
+        ),
+        Lambda {
+            fun_token,
+            param_token,
+            arrow_token,
+            body_expr,
+        } => visitor.visit_lambda(fun_token, param_token, arrow_token, body_expr),
+        Call {
+            callee_expr,
+            open_paren_token,
+            arg_expr,
+            close_paren_token,
+        } => visitor.visit_call(callee_expr, open_paren_token, arg_expr, close_paren_token),
+        BinOp {
+            lhs_expr,
+            binop_token,
+            rhs_expr,
+        } => visitor.visit_binop(lhs_expr, binop_token, rhs_expr),
+        Unary {
+            unop_token,
+            operand_expr,
+        } => visitor.visit_unary(unop_token, operand_expr),
+        Paren {
+            open_paren_token,
+            inner_expr,
+            close_paren_token,
+        } => visitor.visit_paren(open_paren_token, inner_expr, close_paren_token),
+        Error { token } => visitor.visit_error(token),
+        Missing { token } => visitor.visit_missing(token),
+    }
+}
+
+pub fn walk_let<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    let_token: &SyntaxToken,
+    ident_token: &SyntaxToken,
+    equal_token: &SyntaxToken,
+    init_expr: &SyntaxNode,
+    semicolon_token: &SyntaxToken,
+    body_expr: &SyntaxNode,
+) {
+    visitor.visit_token(let_token);
+    visitor.visit_token(ident_token);
+    visitor.visit_token(equal_token);
+    visitor.visit_node(init_expr);
+    visitor.visit_token(semicolon_token);
+    visitor.visit_node(body_expr);
+}
+
+pub fn walk_if<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    if_token: &SyntaxToken,
+    cond_expr: &SyntaxNode,
+    then_token: &SyntaxToken,
+    then_expr: &SyntaxNode,
+    else_token: &SyntaxToken,
+    else_expr: &SyntaxNode,
+) {
+    visitor.visit_token(if_token);
+    visitor.visit_node(cond_expr);
+    visitor.visit_token(then_token);
+    visitor.visit_node(then_expr);
+    visitor.visit_token(else_token);
+    visitor.visit_node(else_expr);
+}
+
+pub fn walk_lambda<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    fun_token: &SyntaxToken,
+    param_token: &SyntaxToken,
+    arrow_token: &SyntaxToken,
+    body_expr: &SyntaxNode,
+) {
+    visitor.visit_token(fun_token);
+    visitor.visit_token(param_token);
+    visitor.visit_token(arrow_token);
+    visitor.visit_node(body_expr);
+}
+
+pub fn walk_call<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    callee_expr: &SyntaxNode,
+    open_paren_token: &SyntaxToken,
+    arg_expr: &SyntaxNode,
+    close_paren_token: &SyntaxToken,
+) {
+    visitor.visit_node(callee_expr);
+    visitor.visit_token(open_paren_token);
+    visitor.visit_node(arg_expr);
+    visitor.visit_token(close_paren_token);
+}
+
+pub fn walk_binop<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    lhs_expr: &SyntaxNode,
+    binop_token: &SyntaxToken,
+    rhs_expr: &SyntaxNode,
+) {
+    visitor.visit_node(lhs_expr);
+    visitor.visit_token(binop_token);
+    visitor.visit_node(rhs_expr);
+}
+
+pub fn walk_unary<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    unop_token: &SyntaxToken,
+    operand_expr: &SyntaxNode,
+) {
+    visitor.visit_token(unop_token);
+    visitor.visit_node(operand_expr);
+}
+
+pub fn walk_paren<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    open_paren_token: &SyntaxToken,
+    inner_expr: &SyntaxNode,
+    close_paren_token: &SyntaxToken,
+) {
+    visitor.visit_token(open_paren_token);
+    visitor.visit_node(inner_expr);
+    visitor.visit_token(close_paren_token);
+}
+
+// 構文木を書き換えながら辿るための trait。使い方は Visitor と同じ
+// 子ノードが持つ幅は、子を訪れた後に walk_node_mut が計算し直す
+pub trait MutVisitor {
+    fn visit_node_mut(&mut self, node: &mut SyntaxNode) {
+        walk_node_mut(self, node)
+    }
+
+    fn visit_int_mut(&mut self, token: &mut SyntaxToken) {
+        self.visit_token_mut(token)
+    }
+
+    fn visit_var_mut(&mut self, token: &mut SyntaxToken) {
+        self.visit_token_mut(token)
+    }
+
+    fn visit_bool_mut(&mut self, token: &mut SyntaxToken) {
+        self.visit_token_mut(token)
+    }
+
+    fn visit_let_mut(
+        &mut self,
+        let_token: &mut SyntaxToken,
+        ident_token: &mut SyntaxToken,
+        equal_token: &mut SyntaxToken,
+        init_expr: &mut SyntaxNode,
+        semicolon_token: &mut SyntaxToken,
+        body_expr: &mut SyntaxNode,
+    ) {
+        walk_let_mut(
+            self,
+            let_token,
+            ident_token,
+            equal_token,
+            init_expr,
+            semicolon_token,
+            body_expr,
+        )
+    }
+
+    fn visit_if_mut(
+        &mut self,
+        if_token: &mut SyntaxToken,
+        cond_expr: &mut SyntaxNode,
+        then_token: &mut SyntaxToken,
+        then_expr: &mut SyntaxNode,
+        else_token: &mut SyntaxToken,
+        else_expr: &mut SyntaxNode,
+    ) {
+        walk_if_mut(
+            self, if_token, cond_expr, then_token, then_expr, else_token, else_expr,
+        )
+    }
+
+    fn visit_lambda_mut(
+        &mut self,
+        fun_token: &mut SyntaxToken,
+        param_token: &mut SyntaxToken,
+        arrow_token: &mut SyntaxToken,
+        body_expr: &mut SyntaxNode,
+    ) {
+        walk_lambda_mut(self, fun_token, param_token, arrow_token, body_expr)
+    }
+
+    fn visit_call_mut(
+        &mut self,
+        callee_expr: &mut SyntaxNode,
+        open_paren_token: &mut SyntaxToken,
+        arg_expr: &mut SyntaxNode,
+        close_paren_token: &mut SyntaxToken,
+    ) {
+        walk_call_mut(
+            self,
+            callee_expr,
+            open_paren_token,
+            arg_expr,
+            close_paren_token,
+        )
+    }
+
+    fn visit_binop_mut(
+        &mut self,
+        lhs_expr: &mut SyntaxNode,
+        binop_token: &mut SyntaxToken,
+        rhs_expr: &mut SyntaxNode,
+    ) {
+        walk_binop_mut(self, lhs_expr, binop_token, rhs_expr)
+    }
+
+    fn visit_unary_mut(&mut self, unop_token: &mut SyntaxToken, operand_expr: &mut SyntaxNode) {
+        walk_unary_mut(self, unop_token, operand_expr)
+    }
+
+    fn visit_paren_mut(
+        &mut self,
+        open_paren_token: &mut SyntaxToken,
+        inner_expr: &mut SyntaxNode,
+        close_paren_token: &mut SyntaxToken,
+    ) {
+        walk_paren_mut(self, open_paren_token, inner_expr, close_paren_token)
+    }
+
+    fn visit_error_mut(&mut self, token: &mut SyntaxToken) {
+        self.visit_token_mut(token)
+    }
+
+    fn visit_missing_mut(&mut self, token: &mut SyntaxToken) {
+        self.visit_token_mut(token)
+    }
+
+    fn visit_token_mut(&mut self, _token: &mut SyntaxToken) {}
+}
+
+pub fn walk_node_mut<V: MutVisitor + ?Sized>(visitor: &mut V, node: &mut SyntaxNode) {
+    use SyntaxNode::*;
+    match node {
+        Int { token } => visitor.visit_int_mut(token),
+        Var { token } => visitor.visit_var_mut(token),
+        Bool { token } => visitor.visit_bool_mut(token),
+        Let {
+            let_token,
+            ident_token,
+            equal_token,
+            init_expr,
+            semicolon_token,
+            body_expr,
+        } => visitor.visit_let_mut(
+            let_token,
+            ident_token,
+            equal_token,
+            &mut init_expr.node,
+            semicolon_token,
+            &mut body_expr.node,
+        ),
+        If {
+            if_token,
+            cond_expr,
+            then_token,
+            then_expr,
+            else_token,
+            else_expr,
+        } => visitor.visit_if_mut(
+            if_token,
+            &mut cond_expr.node,
+            then_token,
+            &mut then_expr.node,
+            else_token,
+            &mut else_expr.node,
+        ),
+        Lambda {
+            fun_token,
+            param_token,
+            arrow_token,
+            body_expr,
+        } => visitor.visit_lambda_mut(fun_token, param_token, arrow_token, &mut body_expr.node),
+        Call {
+            callee_expr,
+            open_paren_token,
+            arg_expr,
+            close_paren_token,
+        } => visitor.visit_call_mut(
+            &mut callee_expr.node,
+            open_paren_token,
+            &mut arg_expr.node,
+            close_paren_token,
+        ),
+        BinOp {
+            lhs_expr,
+            binop_token,
+            rhs_expr,
+        } => visitor.visit_binop_mut(&mut lhs_expr.node, binop_token, &mut rhs_expr.node),
+        Unary {
+            unop_token,
+            operand_expr,
+        } => visitor.visit_unary_mut(unop_token, &mut operand_expr.node),
+        Paren {
+            open_paren_token,
+            inner_expr,
+            close_paren_token,
+        } => visitor.visit_paren_mut(open_paren_token, &mut inner_expr.node, close_paren_token),
+        Error { token } => visitor.visit_error_mut(token),
+        Missing { token } => visitor.visit_missing_mut(token),
+    }
+    // 子ノードの中身が書き換えられたかもしれないので、持っている幅を計算し直す
+    node.update_child_widths();
+}
+
+pub fn walk_let_mut<V: MutVisitor + ?Sized>(
+    visitor: &mut V,
+    let_token: &mut SyntaxToken,
+    ident_token: &mut SyntaxToken,
+    equal_token: &mut SyntaxToken,
+    init_expr: &mut SyntaxNode,
+    semicolon_token: &mut SyntaxToken,
+    body_expr: &mut SyntaxNode,
+) {
+    visitor.visit_token_mut(let_token);
+    visitor.visit_token_mut(ident_token);
+    visitor.visit_token_mut(equal_token);
+    visitor.visit_node_mut(init_expr);
+    visitor.visit_token_mut(semicolon_token);
+    visitor.visit_node_mut(body_expr);
+}
+
+pub fn walk_if_mut<V: MutVisitor + ?Sized>(
+    visitor: &mut V,
+    if_token: &mut SyntaxToken,
+    cond_expr: &mut SyntaxNode,
+    then_token: &mut SyntaxToken,
+    then_expr: &mut SyntaxNode,
+    else_token: &mut SyntaxToken,
+    else_expr: &mut SyntaxNode,
+) {
+    visitor.visit_token_mut(if_token);
+    visitor.visit_node_mut(cond_expr);
+    visitor.visit_token_mut(then_token);
+    visitor.visit_node_mut(then_expr);
+    visitor.visit_token_mut(else_token);
+    visitor.visit_node_mut(else_expr);
+}
+
+pub fn walk_lambda_mut<V: MutVisitor + ?Sized>(
+    visitor: &mut V,
+    fun_token: &mut SyntaxToken,
+    param_token: &mut SyntaxToken,
+    arrow_token: &mut SyntaxToken,
+    body_expr: &mut SyntaxNode,
+) {
+    visitor.visit_token_mut(fun_token);
+    visitor.visit_token_mut(param_token);
+    visitor.visit_token_mut(arrow_token);
+    visitor.visit_node_mut(body_expr);
+}
+
+pub fn walk_call_mut<V: MutVisitor + ?Sized>(
+    visitor: &mut V,
+    callee_expr: &mut SyntaxNode,
+    open_paren_token: &mut SyntaxToken,
+    arg_expr: &mut SyntaxNode,
+    close_paren_token: &mut SyntaxToken,
+) {
+    visitor.visit_node_mut(callee_expr);
+    visitor.visit_token_mut(open_paren_token);
+    visitor.visit_node_mut(arg_expr);
+    visitor.visit_token_mut(close_paren_token);
+}
+
+pub fn walk_binop_mut<V: MutVisitor + ?Sized>(
+    visitor: &mut V,
+    lhs_expr: &mut SyntaxNode,
+    binop_token: &mut SyntaxToken,
+    rhs_expr: &mut SyntaxNode,
+) {
+    visitor.visit_node_mut(lhs_expr);
+    visitor.visit_token_mut(binop_token);
+    visitor.visit_node_mut(rhs_expr);
+}
+
+pub fn walk_unary_mut<V: MutVisitor + ?Sized>(
+    visitor: &mut V,
+    unop_token: &mut SyntaxToken,
+    operand_expr: &mut SyntaxNode,
+) {
+    visitor.visit_token_mut(unop_token);
+    visitor.visit_node_mut(operand_expr);
+}
+
+pub fn walk_paren_mut<V: MutVisitor + ?Sized>(
+    visitor: &mut V,
+    open_paren_token: &mut SyntaxToken,
+    inner_expr: &mut SyntaxNode,
+    close_paren_token: &mut SyntaxToken,
+) {
+    visitor.visit_token_mut(open_paren_token);
+    visitor.visit_node_mut(inner_expr);
+    visitor.visit_token_mut(close_paren_token);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{walk_node, MutVisitor, Visitor};
+    use crate::parse::parse;
+    use crate::source::Source;
+    use crate::syntax_node::{SyntaxNode, SyntaxToken, TokenKind};
+
+    // 変数の出現を数える
+    struct VarCounter(usize);
+
+    impl Visitor for VarCounter {
+        fn visit_var(&mut self, _token: &SyntaxToken) {
+            self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn test_visitor() {
+        let source = Source::from_str("let a = b * (c + 1); a");
+        let (node, _) = parse(&source);
+        let mut counter = VarCounter(0);
+        counter.visit_node(&node);
+        assert_eq!(counter.0, 3);
+    }
+
+    // 括弧の中は数えない
+    struct OutsideParenCounter(usize);
+
+    impl Visitor for OutsideParenCounter {
+        fn visit_node(&mut self, node: &SyntaxNode) {
+            if !matches!(node, SyntaxNode::Paren { .. }) {
+                walk_node(self, node);
+            }
+        }
+
+        fn visit_token(&mut self, token: &SyntaxToken) {
+            if token.kind == TokenKind::Ident {
+                self.0 += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_visitor_skip_children() {
+        let source = Source::from_str("let a = b * (c + 1); a");
+        let (node, _) = parse(&source);
+        let mut counter = OutsideParenCounter(0);
+        counter.visit_node(&node);
+        assert_eq!(counter.0, 3);
+    }
+
+    // 演算子を全て '-' に置き換える
+    struct ToMinus;
+
+    impl MutVisitor for ToMinus {
+        fn visit_binop_mut(
+            &mut self,
+            lhs_expr: &mut SyntaxNode,
+            binop_token: &mut SyntaxToken,
+            rhs_expr: &mut SyntaxNode,
+        ) {
+            binop_token.kind = TokenKind::Minus;
+            super::walk_binop_mut(self, lhs_expr, binop_token, rhs_expr);
+        }
+    }
+
+    #[test]
+    fn test_mut_visitor() {
+        let source = Source::from_str("1 + 2 * (3 / 4)");
+        let (mut node, _) = parse(&source);
+        ToMinus.visit_node_mut(&mut node);
+        let kinds: Vec<_> = node
+            .tokens()
+            .map(|(token, _)| token.kind.clone())
+            .filter(|kind| kind.text().is_some() && *kind != TokenKind::OpenParen)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Minus,
+                TokenKind::Minus,
+                TokenKind::Minus,
+                TokenKind::CloseParen
+            ]
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+// example-codes/annotated 以下のファイルに書かれた注釈を `denvl test` で確かめる
+#[test]
+fn annotations() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let dir = PathBuf::from("example-codes").join("annotated");
+    let mut files: Vec<_> = std::fs::read_dir(manifest_dir.join(&dir))
+        .unwrap()
+        .map(|entry| dir.join(entry.unwrap().file_name()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "denvl"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_denvl"))
+        .arg("test")
+        .args(&files)
+        .current_dir(&manifest_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
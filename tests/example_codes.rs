@@ -0,0 +1,132 @@
+use denvl::ast::Expr;
+use denvl::diagnostic::postprocess;
+use denvl::eval;
+use denvl::syntax_node::dump;
+use denvl::Source;
+use std::path::{Path, PathBuf};
+
+// example-codes 以下の .denvl ファイルを全て解析・評価し、同じ名前の .expected ファイルと比べる
+// DENVL_BLESS=1 を付けて実行すると、比べる代わりに .expected ファイルを今の出力で書き換える
+// e.g. DENVL_BLESS=1 cargo test --test example_codes
+
+const BLESS_ENV: &str = "DENVL_BLESS";
+
+#[test]
+fn example_codes() {
+    let bless = std::env::var_os(BLESS_ENV).is_some_and(|value| value != "0");
+    let mut failures = vec![];
+    for (dir, expect_error) in [("valid", false), ("invalid", true)] {
+        for path in denvl_files(&Path::new("example-codes").join(dir)) {
+            let actual = report(&path);
+            if has_diagnostics(&actual) != expect_error {
+                failures.push(format!(
+                    "{}: expected {}diagnostics\n{actual}",
+                    path.display(),
+                    if expect_error { "" } else { "no " }
+                ));
+            }
+
+            let expected_path = path.with_extension("expected");
+            if bless {
+                std::fs::write(manifest_dir().join(&expected_path), &actual).unwrap();
+                continue;
+            }
+            match std::fs::read_to_string(manifest_dir().join(&expected_path)) {
+                Ok(expected) if expected == actual => (),
+                Ok(expected) => failures.push(format!(
+                    "{}: output differs from {}\n{}",
+                    path.display(),
+                    expected_path.display(),
+                    diff(&expected, &actual)
+                )),
+                Err(_) => failures.push(format!(
+                    "{}: {} not found",
+                    path.display(),
+                    expected_path.display()
+                )),
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\n\nrun with {BLESS_ENV}=1 to update .expected files",
+        failures.join("\n\n")
+    );
+}
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+// manifest_dir からの相対パスを名前順に返す
+fn denvl_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<_> = std::fs::read_dir(manifest_dir().join(dir))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .map(|name| dir.join(name))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "denvl"))
+        .collect();
+    files.sort();
+    files
+}
+
+// .expected ファイルと比べる出力
+// 構文木、診断情報、評価結果を順に出す。診断情報がある場合は評価しない
+fn report(path: &Path) -> String {
+    let source = Source::new(&manifest_dir().join(path)).unwrap();
+    let (syntax_node, diagnostics) = denvl::parse(&source);
+
+    let mut lines = vec![];
+    lines.push("-- tree --".to_string());
+    lines.push(dump::to_sexp(&source, &syntax_node));
+
+    lines.push("-- diagnostics --".to_string());
+    let diagnostics = postprocess::postprocess(diagnostics);
+    for diagnostic in &diagnostics {
+        let (line, column) = source.line_column(diagnostic.range().start);
+        lines.push(format!("{line}:{column}: {}", diagnostic.make_msg()));
+    }
+
+    lines.push("-- result --".to_string());
+    if diagnostics.is_empty() {
+        lines.push(match eval::eval(&source, Expr::root(&syntax_node)) {
+            Ok(value) => value.to_string(),
+            Err(e) => {
+                let (line, column) = source.line_column(e.range().start);
+                format!(
+                    "runtime error: {} at {}:{line}:{column}",
+                    e.message(),
+                    path.display()
+                )
+            }
+        });
+    } else {
+        lines.push("(not evaluated)".to_string());
+    }
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
+
+fn has_diagnostics(report: &str) -> bool {
+    let diagnostics = report
+        .split("-- diagnostics --\n")
+        .nth(1)
+        .and_then(|rest| rest.split("-- result --\n").next())
+        .unwrap_or_default();
+    !diagnostics.is_empty()
+}
+
+// 異なる行だけを並べる
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<_> = expected.lines().collect();
+    let actual: Vec<_> = actual.lines().collect();
+    let mut lines = vec![];
+    for i in 0..expected.len().max(actual.len()) {
+        let (e, a) = (expected.get(i), actual.get(i));
+        if e != a {
+            lines.push(format!("  line {}:", i + 1));
+            lines.push(format!("    - {}", e.unwrap_or(&"<none>")));
+            lines.push(format!("    + {}", a.unwrap_or(&"<none>")));
+        }
+    }
+    lines.join("\n")
+}